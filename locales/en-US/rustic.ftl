@@ -0,0 +1,233 @@
+## Errors
+
+error-bad-argument = Invalid Argument
+error-member-not-found = Member Not Found
+error-member-not-found-description = I couldn't find a member called `{ $member }`.
+error-upstream-unavailable = Service Unavailable
+error-upstream-unavailable-description = `{ $service }` isn't responding right now, please try again later.
+error-image-decode = Invalid Image
+error-image-decode-description = I couldn't read that image.
+error-image-unsupported-description = I can only read PNG, JPEG, WebP, GIF and BMP images.
+error-image-too-big-description = That image has too many pixels, it can have at most { $max } megapixels.
+error-busy = Too Busy
+error-busy-description = I'm working on too many images right now, please try again in a moment.
+error-timed-out = Took Too Long
+error-timed-out-description = That image took too long to process, please try again later.
+error-internal = Unknown Error!
+error-internal-description = Sorry, An unknown error has occured, it has been reported to my developer.
+error-footer = Error ID: { $error_id }
+
+## Hooks
+
+help-aliases = Server Aliases
+hint-usage = **Usage:** `{ $usage }`
+hint-example = **Example:** `{ $example }`
+refusal-blocked = :no_entry: | **Blocked:** You are not allowed to use my commands.
+refusal-disabled = :no_entry: | **Disabled:** `{ $name }` is currently disabled.
+refusal-disabled-here = :no_entry: | **Disabled:** `{ $name }` can't be used here.
+refusal-ratelimited = :hourglass: | **Cooldown:** Try this again in { $seconds } seconds.
+dispatch-check-failed = :no_entry: | **Missing Permissions:** { $reason }
+dispatch-not-enough-arguments = :x: | **Missing Arguments:** This command needs at least { $min } argument(s) but { $given } were given.{ $hint }
+dispatch-too-many-arguments = :x: | **Too Many Arguments:** This command takes at most { $max } argument(s) but { $given } were given.{ $hint }
+dispatch-only-for-guilds = :x: | **Server Only:** This command can only be used in servers.
+dispatch-only-for-dm = :x: | **DM Only:** This command can only be used in direct messages.
+dispatch-only-for-owners = :no_entry: | **Owner Only:** This command can only be used by my developer.
+dispatch-lacking-permissions = :no_entry: | **Missing Permissions:** You need the following permissions to use this command: { $permissions }
+dispatch-lacking-role = :no_entry: | **Missing Role:** You don't have the role required to use this command.
+dispatch-blocked-guild = :no_entry: | **Blocked:** My commands can't be used in this server.
+dispatch-blocked-channel = :no_entry: | **Blocked:** My commands can't be used in this channel.
+check-guild-only = This command can only be used in guilds.
+check-guild-admin = You need the **Manage Server** permission or the bot admin role to use this command.
+
+## Fun
+
+cat-title = Have a cute cat!
+eightball = :8ball: **8ball:** { $answer }
+eightball-1 = It is certain.
+eightball-2 = It is decidedly so.
+eightball-3 = Without a doubt.
+eightball-4 = Yes definitely.
+eightball-5 = You may rely on it.
+eightball-6 = As I see it, yes.
+eightball-7 = Most likely.
+eightball-8 = Outlook good.
+eightball-9 = Yes.
+eightball-10 = Signs point to yes.
+eightball-11 = Reply hazy, try again.
+eightball-12 = Ask again later.
+eightball-13 = Better not tell you now.
+eightball-14 = Cannot predict now.
+eightball-15 = Concentrate and ask again.
+eightball-16 = Don't count on it.
+eightball-17 = My reply is no.
+eightball-18 = My sources say no.
+eightball-19 = Outlook not so good.
+eightball-20 = Very doubtful.
+urban-no-word = Give me a word to look up for.
+urban-no-results = No results found, sorry.
+urban-votes = Votes
+urban-author = by { $author }
+
+## Images
+
+image-download-failed = I couldn't download that image.
+image-not-an-image = That link isn't an image.
+image-link-not-allowed = I can only download images from public https links.
+image-too-large = That image is too large, it can't be bigger than { $max } MB.
+image-gif-flattened = That GIF has more than { $frames } frames or would be bigger than { $max } MB, so I only filtered its first frame.
+
+## Misc
+
+ping-failed = Something went wrong, Please try again later.
+ping-title = Pong!
+ping-latency = **Shard { $shard }**: { $latency }
+about-description =
+    Rustic is an open source multi-purpose bot packed with features
+    You can find my source code on [github](https://github.com/MrArkon/Rustic), Developed by [MrArkon](https://mrarkon.github.io)
+about-guilds = Guilds
+about-users = Users
+about-shard = Shard
+about-memory = Memory Usage
+about-prefix-cache = Prefix Cache
+about-prefix-cache-stats = { $hits } hits / { $misses } misses
+about-footer = Written with Rust & Serenity-rs
+
+## Configuration
+
+prefix-empty = The prefix can't be empty or only whitespace.
+prefix-too-long = The prefix can't be longer than { $max } characters.
+prefix-mentions = The prefix can't contain mentions.
+prefix-missing = Give me a prefix to use.
+prefix-list = The current guild prefixes are: { $prefixes }
+prefix-set = Updated prefix to: `{ $prefix }`
+prefix-exists = `{ $prefix }` is already a prefix.
+prefix-limit = A guild can't have more than { $max } prefixes, remove one first.
+prefix-added = Added prefix: `{ $prefix }`
+prefix-not-found = `{ $prefix }` is not a prefix.
+prefix-remove-last = You can't remove the only prefix, add another one first.
+prefix-removed = Removed prefix: `{ $prefix }`
+prefix-reset = Reset the prefix to the default: `{ $prefix }`
+adminrole-current = The current bot admin role is: { $role }
+adminrole-none = There is no bot admin role, only members with the **Manage Server** permission can configure the bot.
+adminrole-set = Updated the bot admin role to: { $role }
+adminrole-removed = Removed the bot admin role.
+role-not-found = I couldn't find a role called `{ $role }`.
+rules-protected-group = The `{ $group }` group can't be disabled.
+rules-protected-command = Commands in the `{ $group }` group can't be disabled.
+rules-unknown-name = There is no command or group called `{ $name }`.
+rules-target-not-found = I couldn't find a channel or role called `{ $target }`.
+rules-target-guild = this server
+rules-none = There are no command rules, every command can be used everywhere.
+rules-entry-enabled = `{ $name }` is enabled in { $target }
+rules-entry-disabled = `{ $name }` is disabled in { $target }
+rules-silent = Disabled commands are ignored silently.
+rules-enabled = Enabled `{ $name }` in { $target }.
+rules-disabled = Disabled `{ $name }` in { $target }.
+rules-reset = Removed the rule for `{ $name }` in { $target }.
+rules-silent-on = Disabled commands will now be ignored silently.
+rules-silent-off = Members will now be told when a command is disabled.
+rules-silent-invalid = Use `on` or `off`.
+rules-missing-name = Give me a command or group name.
+alias-invalid = An alias can only contain letters, numbers, `-` and `_`, and can't be longer than { $max } characters.
+command-name-taken = `{ $name }` is already the name of a command.
+alias-none = This guild doesn't have any aliases.
+alias-list = The aliases of this guild are: { $aliases }
+alias-unknown-command = There is no command called `{ $command }`.
+alias-limit = A guild can't have more than { $max } aliases, remove one first.
+alias-added = `{ $alias }` is now an alias for `{ $command }`.
+alias-not-found = `{ $alias }` is not an alias.
+alias-removed = Removed alias: `{ $alias }`
+language-current = This guild uses **{ $language }**. Available languages: { $languages }
+language-unknown = I don't speak `{ $language }` yet. Available languages: { $languages }
+language-set = I will now answer in **{ $language }** in this guild.
+rules-target-conflict = Choose either a channel or a role, not both.
+theme-current = Current theme
+theme-updated = Updated the theme
+theme-reset = Reset the theme to the default
+theme-preview = This is how my embeds look in this guild.
+theme-primary = Primary
+theme-error = Error
+theme-success = Success
+theme-unknown-style = `{ $style }` isn't a theme colour, use `primary`, `error` or `success`.
+theme-invalid-colour = `{ $colour }` isn't a colour, use six hex digits like `#F05B4A` or a name like `blurple`.
+theme-footer-too-long = The footer can't be longer than { $max } characters.
+theme-author-too-long = The author name can't be longer than { $max } characters.
+theme-invalid-icon = The icon has to be an `https://` link to an image.
+theme-icon-needs-author = Updated the theme, the icon shows up once you set an author name
+
+## Tags
+
+tag-name-invalid = A tag name can only contain letters, numbers, `-` and `_`, and can't be longer than { $max } characters.
+tag-name-missing = Give me the name of a tag.
+tag-content-missing = Give me the content of the tag.
+tag-too-long = A tag can't be longer than { $max } characters.
+tag-not-found = There is no tag called `{ $name }`.
+tag-not-owned = You don't own the tag `{ $name }`.
+tag-limit = A guild can't have more than { $max } tags, delete one first.
+tag-exists = There already is a tag called `{ $name }`.
+tag-created = Created tag: `{ $name }`
+tag-edited = Edited tag: `{ $name }`
+tag-deleted = Deleted tag: `{ $name }`
+tag-bot-owner = Tags can't be owned by bots.
+tag-transferred = Transferred the tag `{ $name }` to **{ $owner }**.
+tag-info-title = Tag: { $name }
+tag-info-owner = Owner
+tag-info-uses = Uses
+tag-info-created = Created
+tag-info-edited = Edited
+tag-list = Tags ({ $count }): { $tags }
+tag-list-owner = Tags owned by **{ $owner }** ({ $count }): { $tags }
+tag-list-none = This guild doesn't have any tags.
+tag-list-owner-none = **{ $owner }** doesn't own any tags.
+tag-list-more = and { $count } more
+tag-search = Tags matching `{ $text }`: { $tags }
+tag-search-none = No tags matched `{ $text }`.
+
+## Reminders
+
+reminder-invalid-interval = `{ $interval }` isn't an interval, use something like `day`, `week` or `12h`.
+reminder-invalid-time = I couldn't understand when to remind you, use a duration like `2h30m`, a time like `9am` or a date like `2021-12-24 18:00`.
+reminder-missing-content = Tell me what to remind you of.
+reminder-too-long = A reminder can't be longer than { $max } characters.
+reminder-out-of-range = A reminder has to be in the future and less than { $years } years away.
+reminder-interval-too-short = A reminder can't repeat more often than every { $minutes } minutes.
+reminder-limit = You can't have more than { $max } reminders, delete one first.
+reminder-created = I will remind you { $time }. (#{ $id })
+reminder-created-recurring = I will remind you { $time } and then every { $interval }. (#{ $id })
+reminder-none = You don't have any reminders.
+reminder-place-dm = DM
+reminder-list-entry = **#{ $id }** { $time } in { $place }: { $content }
+reminder-every = (every { $interval })
+reminder-list = Your reminders ({ $count })
+reminder-not-found = You don't have a reminder #{ $id }.
+reminder-deleted = Deleted reminder #{ $id }.
+reminder-message = { $user }, you asked me to remind you: { $content }
+reminder-late = Sorry, this reminder was due { $time }.
+reminder-next = Next reminder { $time }, delete it with `remind delete { $id }`.
+
+## Time
+
+timezone-current = Your timezone is **{ $timezone }**, it's { $time } for you.
+timezone-none = You haven't picked a timezone, times you give me are read in UTC. Pick one with `timezone set <city>`.
+timezone-user-current = { $user }'s timezone is **{ $timezone }**, it's { $time } for them.
+timezone-user-none = { $user } hasn't picked a timezone.
+timezone-set = Your timezone is now **{ $timezone }**, it's { $time } for you.
+timezone-reset = Removed your timezone, times you give me are read in UTC again.
+timezone-not-found = I couldn't find the timezone `{ $timezone }`, try a big city near you or a name like `Europe/London`.
+timezone-suggestions = Did you mean { $timezones }?
+time-now = It's { $time } in **{ $timezone }**.
+time-set-hint = Pick your own timezone with `timezone set <city>`.
+time-at = { $time } in **{ $timezone }** is { $timestamp } in your local time.
+time-converted = { $time } in **{ $from }** is { $converted } in **{ $to }**, and { $timestamp } in your local time.
+time-invalid = I couldn't understand that time, use something like `9pm`, `17:30`, `tomorrow 9am` or `2021-12-24 18:00`.
+
+## Personal settings
+
+mylanguage-current = I answer you in **{ $language }**. Available languages: { $languages }
+mylanguage-none = I answer you in the language of each guild. Available languages: { $languages }
+mylanguage-set = I will now always answer you in **{ $language }**.
+mylanguage-reset = I will now answer you in the language of each guild.
+
+## Slash commands
+
+slash-unknown-command = Unknown command.
@@ -0,0 +1,233 @@
+## Errors
+
+error-bad-argument = Argumento no válido
+error-member-not-found = Miembro no encontrado
+error-member-not-found-description = No encontré a ningún miembro llamado `{ $member }`.
+error-upstream-unavailable = Servicio no disponible
+error-upstream-unavailable-description = `{ $service }` no responde ahora mismo, inténtalo de nuevo más tarde.
+error-image-decode = Imagen no válida
+error-image-decode-description = No pude leer esa imagen.
+error-image-unsupported-description = Solo puedo leer imágenes PNG, JPEG, WebP, GIF y BMP.
+error-image-too-big-description = Esa imagen tiene demasiados píxeles, puede tener como máximo { $max } megapíxeles.
+error-busy = Demasiado ocupado
+error-busy-description = Estoy procesando demasiadas imágenes ahora mismo, inténtalo de nuevo en un momento.
+error-timed-out = Tardó demasiado
+error-timed-out-description = Esa imagen tardó demasiado en procesarse, inténtalo de nuevo más tarde.
+error-internal = ¡Error desconocido!
+error-internal-description = Lo siento, ha ocurrido un error desconocido y se ha notificado a mi desarrollador.
+error-footer = ID del error: { $error_id }
+
+## Hooks
+
+help-aliases = Alias del servidor
+hint-usage = **Uso:** `{ $usage }`
+hint-example = **Ejemplo:** `{ $example }`
+refusal-blocked = :no_entry: | **Bloqueado:** No tienes permitido usar mis comandos.
+refusal-disabled = :no_entry: | **Desactivado:** `{ $name }` está desactivado en este momento.
+refusal-disabled-here = :no_entry: | **Desactivado:** `{ $name }` no se puede usar aquí.
+refusal-ratelimited = :hourglass: | **Espera:** Vuelve a intentarlo en { $seconds } segundos.
+dispatch-check-failed = :no_entry: | **Faltan permisos:** { $reason }
+dispatch-not-enough-arguments = :x: | **Faltan argumentos:** Este comando necesita al menos { $min } argumento(s) pero se dieron { $given }.{ $hint }
+dispatch-too-many-arguments = :x: | **Demasiados argumentos:** Este comando acepta como máximo { $max } argumento(s) pero se dieron { $given }.{ $hint }
+dispatch-only-for-guilds = :x: | **Solo en servidores:** Este comando solo se puede usar en servidores.
+dispatch-only-for-dm = :x: | **Solo por MD:** Este comando solo se puede usar en mensajes directos.
+dispatch-only-for-owners = :no_entry: | **Solo el dueño:** Este comando solo lo puede usar mi desarrollador.
+dispatch-lacking-permissions = :no_entry: | **Faltan permisos:** Necesitas los siguientes permisos para usar este comando: { $permissions }
+dispatch-lacking-role = :no_entry: | **Falta el rol:** No tienes el rol necesario para usar este comando.
+dispatch-blocked-guild = :no_entry: | **Bloqueado:** Mis comandos no se pueden usar en este servidor.
+dispatch-blocked-channel = :no_entry: | **Bloqueado:** Mis comandos no se pueden usar en este canal.
+check-guild-only = Este comando solo se puede usar en servidores.
+check-guild-admin = Necesitas el permiso **Gestionar servidor** o el rol de administrador del bot para usar este comando.
+
+## Fun
+
+cat-title = ¡Aquí tienes un gato adorable!
+eightball = :8ball: **8ball:** { $answer }
+eightball-1 = Es cierto.
+eightball-2 = Decididamente sí.
+eightball-3 = Sin duda.
+eightball-4 = Sí, definitivamente.
+eightball-5 = Puedes confiar en ello.
+eightball-6 = Tal y como lo veo, sí.
+eightball-7 = Lo más probable.
+eightball-8 = Buen pronóstico.
+eightball-9 = Sí.
+eightball-10 = Todo apunta a que sí.
+eightball-11 = Respuesta confusa, vuelve a intentarlo.
+eightball-12 = Pregunta de nuevo más tarde.
+eightball-13 = Mejor no te lo digo ahora.
+eightball-14 = No puedo predecirlo ahora.
+eightball-15 = Concéntrate y vuelve a preguntar.
+eightball-16 = No cuentes con ello.
+eightball-17 = Mi respuesta es no.
+eightball-18 = Mis fuentes dicen que no.
+eightball-19 = El pronóstico no es muy bueno.
+eightball-20 = Muy dudoso.
+urban-no-word = Dame una palabra que buscar.
+urban-no-results = No se encontraron resultados, lo siento.
+urban-votes = Votos
+urban-author = por { $author }
+
+## Images
+
+image-download-failed = No pude descargar esa imagen.
+image-not-an-image = Ese enlace no es una imagen.
+image-link-not-allowed = Solo puedo descargar imágenes de enlaces https públicos.
+image-too-large = Esa imagen es demasiado grande, no puede pesar más de { $max } MB.
+image-gif-flattened = Ese GIF tiene más de { $frames } fotogramas o pesaría más de { $max } MB, así que solo filtré su primer fotograma.
+
+## Misc
+
+ping-failed = Algo salió mal, inténtalo de nuevo más tarde.
+ping-title = ¡Pong!
+ping-latency = **Shard { $shard }**: { $latency }
+about-description =
+    Rustic es un bot multipropósito de código abierto lleno de funciones
+    Puedes encontrar mi código fuente en [github](https://github.com/MrArkon/Rustic), desarrollado por [MrArkon](https://mrarkon.github.io)
+about-guilds = Servidores
+about-users = Usuarios
+about-shard = Shard
+about-memory = Uso de memoria
+about-prefix-cache = Caché de prefijos
+about-prefix-cache-stats = { $hits } aciertos / { $misses } fallos
+about-footer = Escrito con Rust y Serenity-rs
+
+## Configuration
+
+prefix-empty = El prefijo no puede estar vacío ni ser solo espacios.
+prefix-too-long = El prefijo no puede tener más de { $max } caracteres.
+prefix-mentions = El prefijo no puede contener menciones.
+prefix-missing = Dame un prefijo que usar.
+prefix-list = Los prefijos actuales del servidor son: { $prefixes }
+prefix-set = Prefijo actualizado a: `{ $prefix }`
+prefix-exists = `{ $prefix }` ya es un prefijo.
+prefix-limit = Un servidor no puede tener más de { $max } prefijos, elimina uno primero.
+prefix-added = Prefijo añadido: `{ $prefix }`
+prefix-not-found = `{ $prefix }` no es un prefijo.
+prefix-remove-last = No puedes eliminar el único prefijo, añade otro primero.
+prefix-removed = Prefijo eliminado: `{ $prefix }`
+prefix-reset = Prefijo restablecido al predeterminado: `{ $prefix }`
+adminrole-current = El rol de administrador del bot actual es: { $role }
+adminrole-none = No hay rol de administrador del bot, solo los miembros con el permiso **Gestionar servidor** pueden configurar el bot.
+adminrole-set = Rol de administrador del bot actualizado a: { $role }
+adminrole-removed = Se eliminó el rol de administrador del bot.
+role-not-found = No encontré ningún rol llamado `{ $role }`.
+rules-protected-group = El grupo `{ $group }` no se puede desactivar.
+rules-protected-command = Los comandos del grupo `{ $group }` no se pueden desactivar.
+rules-unknown-name = No hay ningún comando ni grupo llamado `{ $name }`.
+rules-target-not-found = No encontré ningún canal ni rol llamado `{ $target }`.
+rules-target-guild = este servidor
+rules-none = No hay reglas de comandos, todos los comandos se pueden usar en todas partes.
+rules-entry-enabled = `{ $name }` está activado en { $target }
+rules-entry-disabled = `{ $name }` está desactivado en { $target }
+rules-silent = Los comandos desactivados se ignoran en silencio.
+rules-enabled = Se activó `{ $name }` en { $target }.
+rules-disabled = Se desactivó `{ $name }` en { $target }.
+rules-reset = Se eliminó la regla de `{ $name }` en { $target }.
+rules-silent-on = Los comandos desactivados ahora se ignorarán en silencio.
+rules-silent-off = Ahora se avisará a los miembros cuando un comando esté desactivado.
+rules-silent-invalid = Usa `on` u `off`.
+rules-missing-name = Dame el nombre de un comando o grupo.
+alias-invalid = Un alias solo puede contener letras, números, `-` y `_`, y no puede tener más de { $max } caracteres.
+command-name-taken = `{ $name }` ya es el nombre de un comando.
+alias-none = Este servidor no tiene ningún alias.
+alias-list = Los alias de este servidor son: { $aliases }
+alias-unknown-command = No hay ningún comando llamado `{ $command }`.
+alias-limit = Un servidor no puede tener más de { $max } alias, elimina uno primero.
+alias-added = `{ $alias }` ahora es un alias de `{ $command }`.
+alias-not-found = `{ $alias }` no es un alias.
+alias-removed = Alias eliminado: `{ $alias }`
+language-current = Este servidor usa **{ $language }**. Idiomas disponibles: { $languages }
+language-unknown = Todavía no hablo `{ $language }`. Idiomas disponibles: { $languages }
+language-set = A partir de ahora responderé en **{ $language }** en este servidor.
+rules-target-conflict = Elige un canal o un rol, no ambos.
+theme-current = Tema actual
+theme-updated = Tema actualizado
+theme-reset = Tema restablecido al predeterminado
+theme-preview = Así se ven mis embeds en este servidor.
+theme-primary = Principal
+theme-error = Error
+theme-success = Éxito
+theme-unknown-style = `{ $style }` no es un color del tema, usa `primary`, `error` o `success`.
+theme-invalid-colour = `{ $colour }` no es un color, usa seis dígitos hexadecimales como `#F05B4A` o un nombre como `blurple`.
+theme-footer-too-long = El pie no puede tener más de { $max } caracteres.
+theme-author-too-long = El nombre del autor no puede tener más de { $max } caracteres.
+theme-invalid-icon = El icono tiene que ser un enlace `https://` a una imagen.
+theme-icon-needs-author = Tema actualizado, el icono aparecerá cuando pongas un nombre de autor
+
+## Tags
+
+tag-name-invalid = El nombre de una etiqueta solo puede contener letras, números, `-` y `_`, y no puede tener más de { $max } caracteres.
+tag-name-missing = Dame el nombre de una etiqueta.
+tag-content-missing = Dame el contenido de la etiqueta.
+tag-too-long = Una etiqueta no puede tener más de { $max } caracteres.
+tag-not-found = No hay ninguna etiqueta llamada `{ $name }`.
+tag-not-owned = La etiqueta `{ $name }` no es tuya.
+tag-limit = Un servidor no puede tener más de { $max } etiquetas, elimina una primero.
+tag-exists = Ya existe una etiqueta llamada `{ $name }`.
+tag-created = Etiqueta creada: `{ $name }`
+tag-edited = Etiqueta editada: `{ $name }`
+tag-deleted = Etiqueta eliminada: `{ $name }`
+tag-bot-owner = Las etiquetas no pueden pertenecer a bots.
+tag-transferred = Se transfirió la etiqueta `{ $name }` a **{ $owner }**.
+tag-info-title = Etiqueta: { $name }
+tag-info-owner = Dueño
+tag-info-uses = Usos
+tag-info-created = Creada
+tag-info-edited = Editada
+tag-list = Etiquetas ({ $count }): { $tags }
+tag-list-owner = Etiquetas de **{ $owner }** ({ $count }): { $tags }
+tag-list-none = Este servidor no tiene ninguna etiqueta.
+tag-list-owner-none = **{ $owner }** no tiene ninguna etiqueta.
+tag-list-more = y { $count } más
+tag-search = Etiquetas que coinciden con `{ $text }`: { $tags }
+tag-search-none = Ninguna etiqueta coincide con `{ $text }`.
+
+## Reminders
+
+reminder-invalid-interval = `{ $interval }` no es un intervalo, usa algo como `day`, `week` o `12h`.
+reminder-invalid-time = No entendí cuándo recordártelo, usa una duración como `2h30m`, una hora como `9am` o una fecha como `2021-12-24 18:00`.
+reminder-missing-content = Dime qué quieres que te recuerde.
+reminder-too-long = Un recordatorio no puede tener más de { $max } caracteres.
+reminder-out-of-range = Un recordatorio tiene que ser en el futuro y a menos de { $years } años.
+reminder-interval-too-short = Un recordatorio no puede repetirse más de una vez cada { $minutes } minutos.
+reminder-limit = No puedes tener más de { $max } recordatorios, borra uno primero.
+reminder-created = Te lo recordaré { $time }. (#{ $id })
+reminder-created-recurring = Te lo recordaré { $time } y después cada { $interval }. (#{ $id })
+reminder-none = No tienes recordatorios.
+reminder-place-dm = MD
+reminder-list-entry = **#{ $id }** { $time } en { $place }: { $content }
+reminder-every = (cada { $interval })
+reminder-list = Tus recordatorios ({ $count })
+reminder-not-found = No tienes un recordatorio #{ $id }.
+reminder-deleted = Recordatorio #{ $id } borrado.
+reminder-message = { $user }, me pediste que te recordara: { $content }
+reminder-late = Perdón, este recordatorio era para { $time }.
+reminder-next = Próximo recordatorio { $time }, bórralo con `remind delete { $id }`.
+
+## Time
+
+timezone-current = Tu zona horaria es **{ $timezone }**, para ti son las { $time }.
+timezone-none = No has elegido una zona horaria, las horas que me das se leen en UTC. Elige una con `timezone set <ciudad>`.
+timezone-user-current = La zona horaria de { $user } es **{ $timezone }**, para esa persona son las { $time }.
+timezone-user-none = { $user } no ha elegido una zona horaria.
+timezone-set = Tu zona horaria ahora es **{ $timezone }**, para ti son las { $time }.
+timezone-reset = Quité tu zona horaria, las horas que me das se leen en UTC otra vez.
+timezone-not-found = No encontré la zona horaria `{ $timezone }`, prueba con una ciudad grande cerca de ti o un nombre como `Europe/Madrid`.
+timezone-suggestions = ¿Quisiste decir { $timezones }?
+time-now = Son las { $time } en **{ $timezone }**.
+time-set-hint = Elige tu zona horaria con `timezone set <ciudad>`.
+time-at = { $time } en **{ $timezone }** es { $timestamp } en tu hora local.
+time-converted = { $time } en **{ $from }** es { $converted } en **{ $to }**, y { $timestamp } en tu hora local.
+time-invalid = No entendí esa hora, usa algo como `9pm`, `17:30`, `tomorrow 9am` o `2021-12-24 18:00`.
+
+## Personal settings
+
+mylanguage-current = Te respondo en **{ $language }**. Idiomas disponibles: { $languages }
+mylanguage-none = Te respondo en el idioma de cada servidor. Idiomas disponibles: { $languages }
+mylanguage-set = A partir de ahora siempre te responderé en **{ $language }**.
+mylanguage-reset = A partir de ahora te responderé en el idioma de cada servidor.
+
+## Slash commands
+
+slash-unknown-command = Comando desconocido.
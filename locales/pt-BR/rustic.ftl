@@ -0,0 +1,233 @@
+## Errors
+
+error-bad-argument = Argumento inválido
+error-member-not-found = Membro não encontrado
+error-member-not-found-description = Não encontrei nenhum membro chamado `{ $member }`.
+error-upstream-unavailable = Serviço indisponível
+error-upstream-unavailable-description = `{ $service }` não está respondendo agora, tente novamente mais tarde.
+error-image-decode = Imagem inválida
+error-image-decode-description = Não consegui ler essa imagem.
+error-image-unsupported-description = Só consigo ler imagens PNG, JPEG, WebP, GIF e BMP.
+error-image-too-big-description = Essa imagem tem pixels demais, ela pode ter no máximo { $max } megapixels.
+error-busy = Muito ocupado
+error-busy-description = Estou processando imagens demais agora, tente de novo daqui a pouco.
+error-timed-out = Demorou demais
+error-timed-out-description = Essa imagem demorou demais para ser processada, tente de novo mais tarde.
+error-internal = Erro desconhecido!
+error-internal-description = Desculpe, ocorreu um erro desconhecido e ele foi informado ao meu desenvolvedor.
+error-footer = ID do erro: { $error_id }
+
+## Hooks
+
+help-aliases = Apelidos do servidor
+hint-usage = **Uso:** `{ $usage }`
+hint-example = **Exemplo:** `{ $example }`
+refusal-blocked = :no_entry: | **Bloqueado:** Você não tem permissão para usar meus comandos.
+refusal-disabled = :no_entry: | **Desativado:** `{ $name }` está desativado no momento.
+refusal-disabled-here = :no_entry: | **Desativado:** `{ $name }` não pode ser usado aqui.
+refusal-ratelimited = :hourglass: | **Aguarde:** Tente novamente em { $seconds } segundos.
+dispatch-check-failed = :no_entry: | **Permissões ausentes:** { $reason }
+dispatch-not-enough-arguments = :x: | **Argumentos ausentes:** Este comando precisa de pelo menos { $min } argumento(s), mas { $given } foram dados.{ $hint }
+dispatch-too-many-arguments = :x: | **Argumentos demais:** Este comando aceita no máximo { $max } argumento(s), mas { $given } foram dados.{ $hint }
+dispatch-only-for-guilds = :x: | **Somente em servidores:** Este comando só pode ser usado em servidores.
+dispatch-only-for-dm = :x: | **Somente por DM:** Este comando só pode ser usado em mensagens diretas.
+dispatch-only-for-owners = :no_entry: | **Somente o dono:** Este comando só pode ser usado pelo meu desenvolvedor.
+dispatch-lacking-permissions = :no_entry: | **Permissões ausentes:** Você precisa das seguintes permissões para usar este comando: { $permissions }
+dispatch-lacking-role = :no_entry: | **Cargo ausente:** Você não tem o cargo necessário para usar este comando.
+dispatch-blocked-guild = :no_entry: | **Bloqueado:** Meus comandos não podem ser usados neste servidor.
+dispatch-blocked-channel = :no_entry: | **Bloqueado:** Meus comandos não podem ser usados neste canal.
+check-guild-only = Este comando só pode ser usado em servidores.
+check-guild-admin = Você precisa da permissão **Gerenciar servidor** ou do cargo de administrador do bot para usar este comando.
+
+## Fun
+
+cat-title = Olha um gatinho fofo!
+eightball = :8ball: **8ball:** { $answer }
+eightball-1 = É certo.
+eightball-2 = Decididamente sim.
+eightball-3 = Sem dúvida.
+eightball-4 = Sim, definitivamente.
+eightball-5 = Pode contar com isso.
+eightball-6 = Do meu ponto de vista, sim.
+eightball-7 = Provavelmente.
+eightball-8 = A perspectiva é boa.
+eightball-9 = Sim.
+eightball-10 = Os sinais apontam que sim.
+eightball-11 = Resposta nebulosa, tente novamente.
+eightball-12 = Pergunte novamente mais tarde.
+eightball-13 = Melhor não te dizer agora.
+eightball-14 = Não consigo prever agora.
+eightball-15 = Concentre-se e pergunte novamente.
+eightball-16 = Não conte com isso.
+eightball-17 = Minha resposta é não.
+eightball-18 = Minhas fontes dizem que não.
+eightball-19 = A perspectiva não é muito boa.
+eightball-20 = Muito duvidoso.
+urban-no-word = Me dê uma palavra para procurar.
+urban-no-results = Nenhum resultado encontrado, desculpe.
+urban-votes = Votos
+urban-author = por { $author }
+
+## Images
+
+image-download-failed = Não consegui baixar essa imagem.
+image-not-an-image = Esse link não é uma imagem.
+image-link-not-allowed = Só consigo baixar imagens de links https públicos.
+image-too-large = Essa imagem é grande demais, ela não pode ter mais de { $max } MB.
+image-gif-flattened = Esse GIF tem mais de { $frames } quadros ou ficaria maior que { $max } MB, então só filtrei o primeiro quadro.
+
+## Misc
+
+ping-failed = Algo deu errado, tente novamente mais tarde.
+ping-title = Pong!
+ping-latency = **Shard { $shard }**: { $latency }
+about-description =
+    Rustic é um bot multiuso de código aberto cheio de recursos
+    Você pode encontrar meu código-fonte no [github](https://github.com/MrArkon/Rustic), desenvolvido por [MrArkon](https://mrarkon.github.io)
+about-guilds = Servidores
+about-users = Usuários
+about-shard = Shard
+about-memory = Uso de memória
+about-prefix-cache = Cache de prefixos
+about-prefix-cache-stats = { $hits } acertos / { $misses } falhas
+about-footer = Escrito com Rust e Serenity-rs
+
+## Configuration
+
+prefix-empty = O prefixo não pode estar vazio nem ter só espaços.
+prefix-too-long = O prefixo não pode ter mais de { $max } caracteres.
+prefix-mentions = O prefixo não pode conter menções.
+prefix-missing = Me dê um prefixo para usar.
+prefix-list = Os prefixos atuais do servidor são: { $prefixes }
+prefix-set = Prefixo atualizado para: `{ $prefix }`
+prefix-exists = `{ $prefix }` já é um prefixo.
+prefix-limit = Um servidor não pode ter mais de { $max } prefixos, remova um primeiro.
+prefix-added = Prefixo adicionado: `{ $prefix }`
+prefix-not-found = `{ $prefix }` não é um prefixo.
+prefix-remove-last = Você não pode remover o único prefixo, adicione outro primeiro.
+prefix-removed = Prefixo removido: `{ $prefix }`
+prefix-reset = Prefixo redefinido para o padrão: `{ $prefix }`
+adminrole-current = O cargo de administrador do bot atual é: { $role }
+adminrole-none = Não há cargo de administrador do bot, só membros com a permissão **Gerenciar servidor** podem configurar o bot.
+adminrole-set = Cargo de administrador do bot atualizado para: { $role }
+adminrole-removed = O cargo de administrador do bot foi removido.
+role-not-found = Não encontrei nenhum cargo chamado `{ $role }`.
+rules-protected-group = O grupo `{ $group }` não pode ser desativado.
+rules-protected-command = Os comandos do grupo `{ $group }` não podem ser desativados.
+rules-unknown-name = Não há nenhum comando ou grupo chamado `{ $name }`.
+rules-target-not-found = Não encontrei nenhum canal ou cargo chamado `{ $target }`.
+rules-target-guild = este servidor
+rules-none = Não há regras de comandos, todos os comandos podem ser usados em qualquer lugar.
+rules-entry-enabled = `{ $name }` está ativado em { $target }
+rules-entry-disabled = `{ $name }` está desativado em { $target }
+rules-silent = Comandos desativados são ignorados silenciosamente.
+rules-enabled = `{ $name }` foi ativado em { $target }.
+rules-disabled = `{ $name }` foi desativado em { $target }.
+rules-reset = A regra de `{ $name }` em { $target } foi removida.
+rules-silent-on = Comandos desativados agora serão ignorados silenciosamente.
+rules-silent-off = Agora os membros serão avisados quando um comando estiver desativado.
+rules-silent-invalid = Use `on` ou `off`.
+rules-missing-name = Me dê o nome de um comando ou grupo.
+alias-invalid = Um apelido só pode conter letras, números, `-` e `_`, e não pode ter mais de { $max } caracteres.
+command-name-taken = `{ $name }` já é o nome de um comando.
+alias-none = Este servidor não tem nenhum apelido.
+alias-list = Os apelidos deste servidor são: { $aliases }
+alias-unknown-command = Não há nenhum comando chamado `{ $command }`.
+alias-limit = Um servidor não pode ter mais de { $max } apelidos, remova um primeiro.
+alias-added = `{ $alias }` agora é um apelido de `{ $command }`.
+alias-not-found = `{ $alias }` não é um apelido.
+alias-removed = Apelido removido: `{ $alias }`
+language-current = Este servidor usa **{ $language }**. Idiomas disponíveis: { $languages }
+language-unknown = Ainda não falo `{ $language }`. Idiomas disponíveis: { $languages }
+language-set = A partir de agora vou responder em **{ $language }** neste servidor.
+rules-target-conflict = Escolha um canal ou um cargo, não os dois.
+theme-current = Tema atual
+theme-updated = Tema atualizado
+theme-reset = Tema redefinido para o padrão
+theme-preview = É assim que meus embeds aparecem neste servidor.
+theme-primary = Principal
+theme-error = Erro
+theme-success = Sucesso
+theme-unknown-style = `{ $style }` não é uma cor do tema, use `primary`, `error` ou `success`.
+theme-invalid-colour = `{ $colour }` não é uma cor, use seis dígitos hexadecimais como `#F05B4A` ou um nome como `blurple`.
+theme-footer-too-long = O rodapé não pode ter mais de { $max } caracteres.
+theme-author-too-long = O nome do autor não pode ter mais de { $max } caracteres.
+theme-invalid-icon = O ícone precisa ser um link `https://` para uma imagem.
+theme-icon-needs-author = Tema atualizado, o ícone aparece quando você definir um nome de autor
+
+## Tags
+
+tag-name-invalid = O nome de uma tag só pode conter letras, números, `-` e `_`, e não pode ter mais de { $max } caracteres.
+tag-name-missing = Me dê o nome de uma tag.
+tag-content-missing = Me dê o conteúdo da tag.
+tag-too-long = Uma tag não pode ter mais de { $max } caracteres.
+tag-not-found = Não há nenhuma tag chamada `{ $name }`.
+tag-not-owned = A tag `{ $name }` não é sua.
+tag-limit = Um servidor não pode ter mais de { $max } tags, apague uma primeiro.
+tag-exists = Já existe uma tag chamada `{ $name }`.
+tag-created = Tag criada: `{ $name }`
+tag-edited = Tag editada: `{ $name }`
+tag-deleted = Tag apagada: `{ $name }`
+tag-bot-owner = Tags não podem pertencer a bots.
+tag-transferred = A tag `{ $name }` foi transferida para **{ $owner }**.
+tag-info-title = Tag: { $name }
+tag-info-owner = Dono
+tag-info-uses = Usos
+tag-info-created = Criada
+tag-info-edited = Editada
+tag-list = Tags ({ $count }): { $tags }
+tag-list-owner = Tags de **{ $owner }** ({ $count }): { $tags }
+tag-list-none = Este servidor não tem nenhuma tag.
+tag-list-owner-none = **{ $owner }** não tem nenhuma tag.
+tag-list-more = e mais { $count }
+tag-search = Tags que correspondem a `{ $text }`: { $tags }
+tag-search-none = Nenhuma tag corresponde a `{ $text }`.
+
+## Reminders
+
+reminder-invalid-interval = `{ $interval }` não é um intervalo, use algo como `day`, `week` ou `12h`.
+reminder-invalid-time = Não entendi quando te lembrar, use uma duração como `2h30m`, um horário como `9am` ou uma data como `2021-12-24 18:00`.
+reminder-missing-content = Diga do que eu devo te lembrar.
+reminder-too-long = Um lembrete não pode ter mais de { $max } caracteres.
+reminder-out-of-range = Um lembrete tem que ser no futuro e a menos de { $years } anos.
+reminder-interval-too-short = Um lembrete não pode se repetir mais de uma vez a cada { $minutes } minutos.
+reminder-limit = Você não pode ter mais de { $max } lembretes, apague um primeiro.
+reminder-created = Vou te lembrar { $time }. (#{ $id })
+reminder-created-recurring = Vou te lembrar { $time } e depois a cada { $interval }. (#{ $id })
+reminder-none = Você não tem nenhum lembrete.
+reminder-place-dm = DM
+reminder-list-entry = **#{ $id }** { $time } em { $place }: { $content }
+reminder-every = (a cada { $interval })
+reminder-list = Seus lembretes ({ $count })
+reminder-not-found = Você não tem um lembrete #{ $id }.
+reminder-deleted = Lembrete #{ $id } apagado.
+reminder-message = { $user }, você pediu para eu te lembrar: { $content }
+reminder-late = Desculpe, este lembrete era para { $time }.
+reminder-next = Próximo lembrete { $time }, apague com `remind delete { $id }`.
+
+## Time
+
+timezone-current = Seu fuso horário é **{ $timezone }**, para você são { $time }.
+timezone-none = Você não escolheu um fuso horário, os horários que você me passa são lidos em UTC. Escolha um com `timezone set <cidade>`.
+timezone-user-current = O fuso horário de { $user } é **{ $timezone }**, para essa pessoa são { $time }.
+timezone-user-none = { $user } não escolheu um fuso horário.
+timezone-set = Seu fuso horário agora é **{ $timezone }**, para você são { $time }.
+timezone-reset = Removi seu fuso horário, os horários que você me passa são lidos em UTC de novo.
+timezone-not-found = Não encontrei o fuso horário `{ $timezone }`, tente uma cidade grande perto de você ou um nome como `America/Sao_Paulo`.
+timezone-suggestions = Você quis dizer { $timezones }?
+time-now = São { $time } em **{ $timezone }**.
+time-set-hint = Escolha seu fuso horário com `timezone set <cidade>`.
+time-at = { $time } em **{ $timezone }** é { $timestamp } no seu horário local.
+time-converted = { $time } em **{ $from }** é { $converted } em **{ $to }**, e { $timestamp } no seu horário local.
+time-invalid = Não entendi esse horário, use algo como `9pm`, `17:30`, `tomorrow 9am` ou `2021-12-24 18:00`.
+
+## Personal settings
+
+mylanguage-current = Eu te respondo em **{ $language }**. Idiomas disponíveis: { $languages }
+mylanguage-none = Eu te respondo no idioma de cada servidor. Idiomas disponíveis: { $languages }
+mylanguage-set = A partir de agora vou sempre te responder em **{ $language }**.
+mylanguage-reset = A partir de agora vou te responder no idioma de cada servidor.
+
+## Slash commands
+
+slash-unknown-command = Comando desconhecido.
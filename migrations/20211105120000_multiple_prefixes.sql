@@ -0,0 +1,5 @@
+ALTER TABLE guilds ADD COLUMN IF NOT EXISTS prefixes TEXT[] NOT NULL DEFAULT '{}';
+
+UPDATE guilds SET prefixes = ARRAY[prefix] WHERE prefix IS NOT NULL;
+
+ALTER TABLE guilds DROP COLUMN IF EXISTS prefix;
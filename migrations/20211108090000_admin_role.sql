@@ -0,0 +1 @@
+ALTER TABLE guilds ADD COLUMN IF NOT EXISTS admin_role BIGINT;
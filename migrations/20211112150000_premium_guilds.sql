@@ -0,0 +1 @@
+ALTER TABLE guilds ADD COLUMN IF NOT EXISTS premium BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS blocked_users
+(
+    user_id    BIGINT      NOT NULL PRIMARY KEY,
+    reason     TEXT,
+    blocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS blocked_guilds
+(
+    guild_id   BIGINT      NOT NULL PRIMARY KEY,
+    reason     TEXT,
+    blocked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ
+);
\ No newline at end of file
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS command_rules
+(
+    guild_id  BIGINT  NOT NULL,
+    scope     TEXT    NOT NULL CHECK (scope IN ('guild', 'channel', 'role')),
+    target_id BIGINT  NOT NULL,
+    name      TEXT    NOT NULL,
+    enabled   BOOLEAN NOT NULL,
+    PRIMARY KEY (guild_id, scope, target_id, name)
+);
+
+ALTER TABLE guilds ADD COLUMN IF NOT EXISTS silent_disabled BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
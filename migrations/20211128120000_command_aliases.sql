@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS command_aliases
+(
+    guild_id BIGINT NOT NULL,
+    alias    TEXT   NOT NULL,
+    command  TEXT   NOT NULL,
+    PRIMARY KEY (guild_id, alias)
+);
\ No newline at end of file
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS tags
+(
+    guild_id   BIGINT      NOT NULL,
+    name       TEXT        NOT NULL,
+    content    TEXT        NOT NULL,
+    owner_id   BIGINT      NOT NULL,
+    uses       BIGINT      NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ,
+    PRIMARY KEY (guild_id, name)
+);
\ No newline at end of file
@@ -0,0 +1,7 @@
+ALTER TABLE guilds ADD COLUMN IF NOT EXISTS language TEXT;
+
+CREATE TABLE IF NOT EXISTS users
+(
+    user_id  BIGINT NOT NULL PRIMARY KEY,
+    language TEXT
+);
\ No newline at end of file
@@ -0,0 +1,7 @@
+ALTER TABLE guilds
+    ADD COLUMN IF NOT EXISTS theme_primary     INTEGER,
+    ADD COLUMN IF NOT EXISTS theme_error       INTEGER,
+    ADD COLUMN IF NOT EXISTS theme_success     INTEGER,
+    ADD COLUMN IF NOT EXISTS theme_footer      TEXT,
+    ADD COLUMN IF NOT EXISTS theme_author_name TEXT,
+    ADD COLUMN IF NOT EXISTS theme_author_icon TEXT;
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS reminders
+(
+    id               BIGSERIAL   NOT NULL PRIMARY KEY,
+    user_id          BIGINT      NOT NULL,
+    guild_id         BIGINT,
+    channel_id       BIGINT,
+    content          TEXT        NOT NULL,
+    due_at           TIMESTAMPTZ NOT NULL,
+    interval_seconds BIGINT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS reminders_due_at ON reminders (due_at);
+CREATE INDEX IF NOT EXISTS reminders_user_id ON reminders (user_id);
\ No newline at end of file
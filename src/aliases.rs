@@ -0,0 +1,149 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Command aliases defined by each guild, expanded before the framework sees the message.
+
+use crate::{guild_prefix, AliasCacheContainer, PgPoolContainer};
+use dashmap::DashMap;
+use serenity::{
+    model::{channel::Message, id::GuildId},
+    prelude::Context,
+};
+use sqlx::{query, PgPool};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::error;
+
+/// Alias to command name, both lowercase.
+pub type Aliases = BTreeMap<String, String>;
+
+/// Per-guild cache of the command aliases, like the prefix cache.
+#[derive(Default)]
+pub struct AliasCache {
+    guilds: DashMap<GuildId, Arc<Aliases>>,
+}
+
+impl AliasCache {
+    pub async fn get(&self, pool: &PgPool, guild_id: GuildId) -> Arc<Aliases> {
+        if let Some(aliases) = self.guilds.get(&guild_id) {
+            return aliases.clone();
+        }
+
+        match query!(
+            "SELECT alias, command FROM command_aliases WHERE guild_id=$1 ORDER BY alias",
+            guild_id.0 as i64
+        )
+        .fetch_all(pool)
+        .await
+        {
+            Ok(rows) => {
+                let aliases = Arc::new(
+                    rows.into_iter()
+                        .map(|row| (row.alias, row.command))
+                        .collect::<Aliases>(),
+                );
+                self.guilds.insert(guild_id, aliases.clone());
+                aliases
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next message retries the database
+                error!("Couldn't query database for command aliases: {}", why);
+                Arc::new(Aliases::new())
+            }
+        }
+    }
+
+    pub fn invalidate(&self, guild_id: GuildId) {
+        self.guilds.remove(&guild_id);
+    }
+}
+
+pub async fn set_alias(
+    pool: &PgPool,
+    guild_id: GuildId,
+    alias: &str,
+    command: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO command_aliases (guild_id, alias, command) VALUES ($1, $2, $3) ON CONFLICT (guild_id, alias) DO UPDATE SET command = $3",
+        guild_id.0 as i64,
+        alias,
+        command
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove an alias, returning false if the guild didn't have it.
+pub async fn remove_alias(
+    pool: &PgPool,
+    guild_id: GuildId,
+    alias: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = query!(
+        "DELETE FROM command_aliases WHERE guild_id=$1 AND alias=$2",
+        guild_id.0 as i64,
+        alias
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace a guild alias at the start of a command with the command it stands for.
+pub async fn expand(ctx: &Context, msg: &mut Message) {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+
+    let prefix = guild_prefix(ctx, msg).await;
+    let bot_id = ctx.cache.current_user_id().await;
+    let mentions = [format!("<@{}>", bot_id.0), format!("<@!{}>", bot_id.0)];
+
+    let prefix_len = match std::iter::once(&prefix)
+        .chain(mentions.iter())
+        .find(|prefix| msg.content.starts_with(prefix.as_str()))
+    {
+        Some(prefix) => prefix.len(),
+        None => return,
+    };
+
+    let rest = &msg.content[prefix_len..];
+    let name_start = prefix_len + (rest.len() - rest.trim_start().len());
+    let name_end = msg.content[name_start..]
+        .find(char::is_whitespace)
+        .map_or(msg.content.len(), |end| name_start + end);
+
+    if name_start == name_end {
+        return;
+    }
+
+    let (pool, alias_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<AliasCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    let aliases = alias_cache.get(&pool, guild_id).await;
+
+    if let Some(command) = aliases.get(&msg.content[name_start..name_end].to_lowercase()) {
+        msg.content.replace_range(name_start..name_end, command);
+    }
+}
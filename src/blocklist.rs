@@ -0,0 +1,162 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use serenity::model::id::{GuildId, UserId};
+use sqlx::{query, PgPool};
+use std::collections::HashSet;
+
+/// Something which can be blocked from using the bot.
+#[derive(Clone, Copy)]
+pub enum Target {
+    User(UserId),
+    Guild(GuildId),
+}
+
+pub struct Entry {
+    pub id: u64,
+    pub reason: Option<String>,
+    pub blocked_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The users and guilds currently blocked, along with the time the next block expires.
+#[derive(Default)]
+pub struct Blocked {
+    pub users: HashSet<UserId>,
+    pub guilds: HashSet<GuildId>,
+    pub next_expiry: Option<DateTime<Utc>>,
+}
+
+pub async fn active(pool: &PgPool) -> Result<Blocked, sqlx::Error> {
+    let mut blocked = Blocked::default();
+    let mut expiries = Vec::new();
+
+    let users = query!(
+        "SELECT user_id, expires_at FROM blocked_users WHERE expires_at IS NULL OR expires_at > NOW()"
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in users {
+        blocked.users.insert(UserId(row.user_id as u64));
+        expiries.extend(row.expires_at);
+    }
+
+    let guilds = query!(
+        "SELECT guild_id, expires_at FROM blocked_guilds WHERE expires_at IS NULL OR expires_at > NOW()"
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in guilds {
+        blocked.guilds.insert(GuildId(row.guild_id as u64));
+        expiries.extend(row.expires_at);
+    }
+
+    blocked.next_expiry = expiries.into_iter().min();
+
+    Ok(blocked)
+}
+
+pub async fn block(
+    pool: &PgPool,
+    target: Target,
+    reason: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    match target {
+        Target::User(user_id) => {
+            query!(
+                "INSERT INTO blocked_users (user_id, reason, blocked_at, expires_at) VALUES ($1, $2, NOW(), $3) ON CONFLICT (user_id) DO UPDATE SET reason = $2, blocked_at = NOW(), expires_at = $3",
+                user_id.0 as i64,
+                reason,
+                expires_at
+            )
+            .execute(pool)
+            .await?;
+        }
+        Target::Guild(guild_id) => {
+            query!(
+                "INSERT INTO blocked_guilds (guild_id, reason, blocked_at, expires_at) VALUES ($1, $2, NOW(), $3) ON CONFLICT (guild_id) DO UPDATE SET reason = $2, blocked_at = NOW(), expires_at = $3",
+                guild_id.0 as i64,
+                reason,
+                expires_at
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove a block, returning false if the target wasn't blocked.
+pub async fn unblock(pool: &PgPool, target: Target) -> Result<bool, sqlx::Error> {
+    let result = match target {
+        Target::User(user_id) => {
+            query!(
+                "DELETE FROM blocked_users WHERE user_id=$1",
+                user_id.0 as i64
+            )
+            .execute(pool)
+            .await?
+        }
+        Target::Guild(guild_id) => {
+            query!(
+                "DELETE FROM blocked_guilds WHERE guild_id=$1",
+                guild_id.0 as i64
+            )
+            .execute(pool)
+            .await?
+        }
+    };
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_users(pool: &PgPool) -> Result<Vec<Entry>, sqlx::Error> {
+    let rows = query!(
+        "SELECT user_id, reason, blocked_at, expires_at FROM blocked_users WHERE expires_at IS NULL OR expires_at > NOW() ORDER BY blocked_at DESC"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Entry {
+            id: row.user_id as u64,
+            reason: row.reason,
+            blocked_at: row.blocked_at,
+            expires_at: row.expires_at,
+        })
+        .collect())
+}
+
+pub async fn list_guilds(pool: &PgPool) -> Result<Vec<Entry>, sqlx::Error> {
+    let rows = query!(
+        "SELECT guild_id, reason, blocked_at, expires_at FROM blocked_guilds WHERE expires_at IS NULL OR expires_at > NOW() ORDER BY blocked_at DESC"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Entry {
+            id: row.guild_id as u64,
+            reason: row.reason,
+            blocked_at: row.blocked_at,
+            expires_at: row.expires_at,
+        })
+        .collect())
+}
@@ -0,0 +1,121 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use dashmap::DashMap;
+use serenity::model::id::GuildId;
+use sqlx::{query, PgPool};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::error;
+
+pub const DEFAULT_PREFIX: &str = "~";
+
+/// Per-guild prefix cache sitting in front of the `guilds` table.
+#[derive(Default)]
+pub struct PrefixCache {
+    prefixes: DashMap<GuildId, Vec<String>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// A guild without any prefixes configured uses the default one.
+fn or_default(prefixes: Vec<String>) -> Vec<String> {
+    if prefixes.is_empty() {
+        vec![DEFAULT_PREFIX.to_string()]
+    } else {
+        prefixes
+    }
+}
+
+impl PrefixCache {
+    /// Get the prefixes of a guild, querying the database on a cache miss.
+    pub async fn get(&self, pool: &PgPool, guild_id: GuildId) -> Vec<String> {
+        if let Some(prefixes) = self.prefixes.get(&guild_id) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return prefixes.clone();
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        match query!(
+            "SELECT prefixes FROM guilds WHERE guild_id=$1",
+            guild_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await
+        {
+            Ok(response) => {
+                let prefixes =
+                    or_default(response.map(|result| result.prefixes).unwrap_or_default());
+                self.prefixes.insert(guild_id, prefixes.clone());
+                prefixes
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next message retries the database
+                error!("Couldn't query database for prefixes: {}", why);
+                vec![DEFAULT_PREFIX.to_string()]
+            }
+        }
+    }
+
+    /// Load the prefixes of the given guilds in a single query.
+    pub async fn prewarm(&self, pool: &PgPool, guilds: &[GuildId]) -> Result<(), sqlx::Error> {
+        let ids = guilds.iter().map(|id| id.0 as i64).collect::<Vec<i64>>();
+
+        let rows = query!(
+            "SELECT guild_id, prefixes FROM guilds WHERE guild_id = ANY($1)",
+            &ids[..]
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // A lookup during the query may have cached a newer value, never replace it
+        let mut found = HashSet::new();
+        for row in rows {
+            let guild_id = GuildId(row.guild_id as u64);
+            found.insert(guild_id);
+            self.prefixes
+                .entry(guild_id)
+                .or_insert_with(|| or_default(row.prefixes));
+        }
+
+        for guild_id in guilds.iter().filter(|guild_id| !found.contains(guild_id)) {
+            self.prefixes
+                .entry(*guild_id)
+                .or_insert_with(|| vec![DEFAULT_PREFIX.to_string()]);
+        }
+
+        Ok(())
+    }
+
+    /// Drop the cached prefix of a guild, the next lookup will hit the database.
+    pub fn invalidate(&self, guild_id: GuildId) {
+        self.prefixes.remove(&guild_id);
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn cached_guilds(&self) -> usize {
+        self.prefixes.len()
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::PgPoolContainer;
+use serenity::{
+    framework::standard::{macros::check, Args, CommandOptions, Reason},
+    model::prelude::{GuildId, Member, Message, RoleId},
+    prelude::Context,
+};
+use sqlx::query;
+use tracing::error;
+
+/// Get the role allowed to configure the bot in a guild, if one has been set.
+pub async fn admin_role(ctx: &Context, guild_id: GuildId) -> Option<RoleId> {
+    let pool = {
+        let data = ctx.data.read().await;
+        data.get::<PgPoolContainer>().unwrap().clone()
+    };
+
+    match query!(
+        "SELECT admin_role FROM guilds WHERE guild_id=$1",
+        guild_id.0 as i64
+    )
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(response) => response
+            .and_then(|result| result.admin_role)
+            .map(|id| RoleId(id as u64)),
+        Err(why) => {
+            error!("Couldn't query database for the admin role: {}", why);
+            None
+        }
+    }
+}
+
+/// A member is a guild admin if they can manage the guild or have the bot admin role.
+pub async fn is_guild_admin(ctx: &Context, guild_id: GuildId, member: &Member) -> bool {
+    if let Ok(permissions) = member.permissions(ctx).await {
+        if permissions.manage_guild() {
+            return true;
+        }
+    }
+
+    match admin_role(ctx, guild_id).await {
+        Some(role) => member.roles.contains(&role),
+        None => false,
+    }
+}
+
+#[check]
+#[name = "GuildAdmin"]
+async fn guild_admin_check(
+    ctx: &Context,
+    msg: &Message,
+    _: &mut Args,
+    _: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild_id = match msg.guild_id {
+        Some(id) => id,
+        None => return Err(Reason::User("check-guild-only".to_string())),
+    };
+
+    let member = match msg.member(ctx).await {
+        Ok(member) => member,
+        Err(why) => return Err(Reason::Log(format!("Couldn't get the member: {:?}", why))),
+    };
+
+    if is_guild_admin(ctx, guild_id, &member).await {
+        Ok(())
+    } else {
+        Err(Reason::User("check-guild-admin".to_string()))
+    }
+}
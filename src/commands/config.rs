@@ -13,70 +13,1164 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::PgPoolContainer;
-use log::error;
+use super::Response;
+use crate::{
+    aliases::{self, Aliases},
+    cache::DEFAULT_PREFIX,
+    checks::admin_role,
+    error::RusticError,
+    find_command,
+    i18n::{self, language, t, Lang},
+    is_command_name,
+    rules::{self, RuleTarget},
+    theme::{self, format_colour, parse_colour, Style, Theme},
+    AliasCacheContainer, LanguageCacheContainer, PgPoolContainer, PrefixCacheContainer,
+    RuleCacheContainer, ThemeCacheContainer, GROUPS,
+};
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
-    model::prelude::Message,
+    model::prelude::{GuildChannel, GuildId, Message, Role, RoleId},
     prelude::Context,
+    utils::ArgumentConvert,
 };
 use sqlx::query;
+use std::sync::Arc;
+
+const MAX_PREFIX_LENGTH: usize = 16;
+const MAX_PREFIXES: usize = 5;
+
+/// Groups which can't be disabled, so a guild can't lock itself out of the configuration.
+const PROTECTED_GROUPS: &[&str] = &["Configuration", "Owner"];
+
+const MAX_ALIAS_LENGTH: usize = 32;
+const MAX_ALIASES: usize = 50;
+
+const MAX_FOOTER_LENGTH: usize = 200;
+const MAX_AUTHOR_LENGTH: usize = 64;
+const MAX_ICON_URL_LENGTH: usize = 512;
+
+/// Check that a prefix is usable, returning the reason it isn't otherwise.
+fn validate_prefix(lang: Lang, prefix: &str) -> Result<(), RusticError> {
+    if prefix.trim().is_empty() {
+        return Err(RusticError::BadArgument(t!(lang, "prefix-empty")));
+    }
+
+    if prefix.chars().count() > MAX_PREFIX_LENGTH {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "prefix-too-long",
+            max = MAX_PREFIX_LENGTH
+        )));
+    }
+
+    if prefix.contains("<@") || prefix.contains("@everyone") || prefix.contains("@here") {
+        return Err(RusticError::BadArgument(t!(lang, "prefix-mentions")));
+    }
+
+    Ok(())
+}
+
+fn single_prefix(lang: Lang, args: &mut Args) -> Result<String, RusticError> {
+    args.single_quoted::<String>()
+        .map_err(|_| RusticError::BadArgument(t!(lang, "prefix-missing")))
+}
+
+async fn get_prefixes(ctx: &Context, guild_id: GuildId) -> Vec<String> {
+    let (pool, prefix_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<PrefixCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    prefix_cache.get(&pool, guild_id).await
+}
+
+async fn set_prefixes(
+    ctx: &Context,
+    guild_id: GuildId,
+    prefixes: &[String],
+) -> Result<(), RusticError> {
+    let (pool, prefix_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<PrefixCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    query!(
+        "INSERT INTO guilds (guild_id, prefixes) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET prefixes = $2",
+        guild_id.0 as i64,
+        prefixes
+    )
+    .execute(&pool)
+    .await?;
+
+    prefix_cache.invalidate(guild_id);
+
+    Ok(())
+}
+
+pub async fn prefix_list_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+) -> Result<Response, RusticError> {
+    let prefixes = get_prefixes(ctx, guild_id).await;
+
+    Ok(Response::Content(t!(
+        lang,
+        "prefix-list",
+        prefixes = prefixes
+            .iter()
+            .map(|prefix| format!("`{}`", prefix))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )))
+}
+
+pub async fn prefix_set_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    new_prefix: String,
+) -> Result<Response, RusticError> {
+    validate_prefix(lang, &new_prefix)?;
+
+    set_prefixes(ctx, guild_id, std::slice::from_ref(&new_prefix)).await?;
+
+    Ok(Response::Content(t!(
+        lang,
+        "prefix-set",
+        prefix = new_prefix
+    )))
+}
+
+pub async fn prefix_add_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    new_prefix: String,
+) -> Result<Response, RusticError> {
+    validate_prefix(lang, &new_prefix)?;
+
+    let mut prefixes = get_prefixes(ctx, guild_id).await;
+
+    if prefixes.contains(&new_prefix) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "prefix-exists",
+            prefix = new_prefix
+        )));
+    }
+
+    if prefixes.len() >= MAX_PREFIXES {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "prefix-limit",
+            max = MAX_PREFIXES
+        )));
+    }
+
+    prefixes.push(new_prefix.clone());
+    set_prefixes(ctx, guild_id, &prefixes).await?;
+
+    Ok(Response::Content(t!(
+        lang,
+        "prefix-added",
+        prefix = new_prefix
+    )))
+}
+
+pub async fn prefix_remove_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    old_prefix: String,
+) -> Result<Response, RusticError> {
+    let mut prefixes = get_prefixes(ctx, guild_id).await;
+
+    if !prefixes.contains(&old_prefix) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "prefix-not-found",
+            prefix = old_prefix
+        )));
+    }
+
+    if prefixes.len() == 1 {
+        return Err(RusticError::BadArgument(t!(lang, "prefix-remove-last")));
+    }
+
+    prefixes.retain(|prefix| prefix != &old_prefix);
+    set_prefixes(ctx, guild_id, &prefixes).await?;
+
+    Ok(Response::Content(t!(
+        lang,
+        "prefix-removed",
+        prefix = old_prefix
+    )))
+}
+
+pub async fn prefix_reset_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+) -> Result<Response, RusticError> {
+    set_prefixes(ctx, guild_id, &[]).await?;
+
+    Ok(Response::Content(t!(
+        lang,
+        "prefix-reset",
+        prefix = DEFAULT_PREFIX
+    )))
+}
 
 #[command]
 #[only_in(guilds)]
 #[usage = "[prefix]"]
-#[description = "Not providing the prefix will show the current prefix. Providing the prefix will set the it to that prefix. To use spaces in your prefix surround it with double quotation marks \"an example \""]
+#[example = "!"]
+#[sub_commands(prefix_add, prefix_remove, prefix_list, prefix_reset)]
+#[description = "Not providing the prefix will show the current prefixes. Providing the prefix will replace all the prefixes with that prefix. To use spaces in your prefix surround it with double quotation marks \"an example \""]
 async fn prefix(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    if args.is_empty() {
-        let prefix;
-        let guild_id = msg.guild_id.unwrap().0 as i64;
+    let guild_id = msg.guild_id.unwrap();
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    let response = if args.is_empty() {
+        prefix_list_response(ctx, lang, guild_id).await?
+    } else {
+        prefix_set_response(ctx, lang, guild_id, single_prefix(lang, &mut args)?).await?
+    };
+
+    response.send(ctx, msg).await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<prefix>"]
+#[example = "?"]
+#[description = "Add another prefix the bot will respond to."]
+async fn prefix_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    prefix_add_response(
+        ctx,
+        lang,
+        msg.guild_id.unwrap(),
+        single_prefix(lang, &mut args)?,
+    )
+    .await?
+    .send(ctx, msg)
+    .await
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<prefix>"]
+#[example = "?"]
+#[description = "Remove one of the prefixes."]
+async fn prefix_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    prefix_remove_response(
+        ctx,
+        lang,
+        msg.guild_id.unwrap(),
+        single_prefix(lang, &mut args)?,
+    )
+    .await?
+    .send(ctx, msg)
+    .await
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[description = "List all the prefixes of this guild."]
+async fn prefix_list(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
 
-        let pool = {
-            let data = ctx.data.read().await;
-            data.get::<PgPoolContainer>().unwrap().clone()
-        };
+    prefix_list_response(ctx, lang, msg.guild_id.unwrap())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("reset")]
+#[only_in(guilds)]
+#[description = "Reset the prefixes back to the default."]
+async fn prefix_reset(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    prefix_reset_response(ctx, lang, msg.guild_id.unwrap())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+pub async fn adminrole_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    new_role: Option<Option<RoleId>>,
+) -> Result<Response, RusticError> {
+    let new_role = match new_role {
+        Some(new_role) => new_role,
+        None => {
+            return Ok(Response::Content(match admin_role(ctx, guild_id).await {
+                Some(role) => t!(lang, "adminrole-current", role = format!("<@&{}>", role.0)),
+                None => t!(lang, "adminrole-none"),
+            }))
+        }
+    };
+
+    let pool = {
+        let data = ctx.data.read().await;
+        data.get::<PgPoolContainer>().unwrap().clone()
+    };
+
+    query!(
+        "INSERT INTO guilds (guild_id, admin_role) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET admin_role = $2",
+        guild_id.0 as i64,
+        new_role.map(|role| role.0 as i64)
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(Response::Content(match new_role {
+        Some(role) => t!(lang, "adminrole-set", role = format!("<@&{}>", role.0)),
+        None => t!(lang, "adminrole-removed"),
+    }))
+}
+
+#[command]
+#[only_in(guilds)]
+#[usage = "[role|none]"]
+#[example = "@Moderators"]
+#[description = "Not providing the role will show the current bot admin role. Providing a role will allow members with that role to configure the bot, `none` removes it."]
+async fn adminrole(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
 
-        match query!("SELECT prefix FROM guilds WHERE guild_id=$1", guild_id)
-            .fetch_optional(&pool)
+    let new_role = if args.is_empty() {
+        None
+    } else if args.message().eq_ignore_ascii_case("none") {
+        Some(None)
+    } else {
+        let role = <Role as ArgumentConvert>::convert(
+            ctx,
+            msg.guild_id,
+            Some(msg.channel_id),
+            args.message(),
+        )
+        .await
+        .map_err(|_| RusticError::BadArgument(t!(lang, "role-not-found", role = args.message())))?;
+        Some(Some(role.id))
+    };
+
+    adminrole_response(ctx, lang, msg.guild_id.unwrap(), new_role)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+/// Resolve the command or group a rule is for, rules always use the lowercase primary name.
+fn rule_name(lang: Lang, name: &str) -> Result<String, RusticError> {
+    if let Some(group) = GROUPS
+        .iter()
+        .find(|group| group.name.eq_ignore_ascii_case(name))
+    {
+        if PROTECTED_GROUPS.contains(&group.name) {
+            return Err(RusticError::BadArgument(t!(
+                lang,
+                "rules-protected-group",
+                group = group.name
+            )));
+        }
+
+        return Ok(group.name.to_lowercase());
+    }
+
+    match find_command(name) {
+        Some((group, _)) if PROTECTED_GROUPS.contains(&group.name) => Err(
+            RusticError::BadArgument(t!(lang, "rules-protected-command", group = group.name)),
+        ),
+        Some((_, command)) => Ok(command.options.names[0].to_lowercase()),
+        None => Err(RusticError::BadArgument(t!(
+            lang,
+            "rules-unknown-name",
+            name = name
+        ))),
+    }
+}
+
+/// Parse the optional channel or role a rule applies to, the whole guild otherwise.
+async fn rule_target(
+    ctx: &Context,
+    lang: Lang,
+    msg: &Message,
+    args: &Args,
+) -> Result<RuleTarget, RusticError> {
+    let target = args.rest();
+
+    if target.is_empty() {
+        return Ok(RuleTarget::Guild);
+    }
+
+    if let Ok(channel) =
+        <GuildChannel as ArgumentConvert>::convert(ctx, msg.guild_id, Some(msg.channel_id), target)
             .await
-        {
-            Ok(response) => {
-                prefix = if let Some(result) = response {
-                    result.prefix.unwrap_or_else(|| "~".to_string())
-                } else {
-                    "~".to_string()
-                };
-            }
-            Err(why) => {
-                error!("Couldn't query database for prefix: {}", why);
-                prefix = "~".to_string();
-            }
-        };
-
-        msg.channel_id
-            .say(
-                &ctx.http,
-                format!("The current guild prefix is: `{}`", prefix),
+    {
+        return Ok(RuleTarget::Channel(channel.id));
+    }
+
+    if let Ok(role) =
+        <Role as ArgumentConvert>::convert(ctx, msg.guild_id, Some(msg.channel_id), target).await
+    {
+        return Ok(RuleTarget::Role(role.id));
+    }
+
+    Err(RusticError::BadArgument(t!(
+        lang,
+        "rules-target-not-found",
+        target = target
+    )))
+}
+
+pub async fn commands_list_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+) -> Result<Response, RusticError> {
+    let (pool, rule_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<RuleCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    let guild_rules = rule_cache.get(&pool, guild_id).await;
+
+    if guild_rules.rules.is_empty() {
+        return Ok(Response::Content(t!(lang, "rules-none")));
+    }
+
+    let mut content = guild_rules
+        .rules
+        .iter()
+        .map(|rule| {
+            let id = if rule.enabled {
+                "rules-entry-enabled"
+            } else {
+                "rules-entry-disabled"
+            };
+            t!(
+                lang,
+                id,
+                name = rule.name.as_str(),
+                target = rule.target.mention(lang)
             )
-            .await?;
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
 
-        return Ok(());
+    if guild_rules.silent {
+        content.push_str(&format!("\n\n{}", t!(lang, "rules-silent")));
     }
 
-    let new_prefix = args.single_quoted::<String>().unwrap();
-    let pool = {
+    Ok(Response::Content(content))
+}
+
+pub async fn commands_set_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    name: &str,
+    target: RuleTarget,
+    enabled: Option<bool>,
+) -> Result<Response, RusticError> {
+    let name = rule_name(lang, name)?;
+
+    let (pool, rule_cache) = {
         let data = ctx.data.read().await;
-        data.get::<PgPoolContainer>().unwrap().clone()
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<RuleCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    rules::set_rule(&pool, guild_id, target, &name, enabled).await?;
+    rule_cache.invalidate(guild_id);
+
+    let id = match enabled {
+        Some(true) => "rules-enabled",
+        Some(false) => "rules-disabled",
+        None => "rules-reset",
+    };
+
+    Ok(Response::Content(t!(
+        lang,
+        id,
+        name = name,
+        target = target.mention(lang)
+    )))
+}
+
+pub async fn commands_silent_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    silent: bool,
+) -> Result<Response, RusticError> {
+    let (pool, rule_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<RuleCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    rules::set_silent(&pool, guild_id, silent).await?;
+    rule_cache.invalidate(guild_id);
+
+    Ok(Response::Content(if silent {
+        t!(lang, "rules-silent-on")
+    } else {
+        t!(lang, "rules-silent-off")
+    }))
+}
+
+async fn commands_set(
+    ctx: &Context,
+    msg: &Message,
+    mut args: Args,
+    enabled: Option<bool>,
+) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = args
+        .single::<String>()
+        .map_err(|_| RusticError::BadArgument(t!(lang, "rules-missing-name")))?;
+    let target = rule_target(ctx, lang, msg, &args).await?;
+
+    commands_set_response(ctx, lang, msg.guild_id.unwrap(), &name, target, enabled)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command]
+#[only_in(guilds)]
+#[sub_commands(commands_disable, commands_enable, commands_reset, commands_silent)]
+#[description = "Show where commands and groups have been enabled or disabled."]
+async fn commands(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    commands_list_response(ctx, lang, msg.guild_id.unwrap())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("disable")]
+#[only_in(guilds)]
+#[min_args(1)]
+#[usage = "<command|group> [#channel|@role]"]
+#[example = "fun #general"]
+#[description = "Disable a command or a whole group in this server, a channel or for a role. Channel rules win over role rules, which win over server rules."]
+async fn commands_disable(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    commands_set(ctx, msg, args, Some(false)).await
+}
+
+#[command("enable")]
+#[only_in(guilds)]
+#[min_args(1)]
+#[usage = "<command|group> [#channel|@role]"]
+#[example = "cat #memes"]
+#[description = "Enable a command or a whole group in this server, a channel or for a role, overriding broader rules."]
+async fn commands_enable(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    commands_set(ctx, msg, args, Some(true)).await
+}
+
+#[command("reset")]
+#[only_in(guilds)]
+#[min_args(1)]
+#[usage = "<command|group> [#channel|@role]"]
+#[example = "fun #general"]
+#[description = "Remove a rule, the command falls back to the broader rules."]
+async fn commands_reset(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    commands_set(ctx, msg, args, None).await
+}
+
+#[command("silent")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<on|off>"]
+#[example = "on"]
+#[description = "Ignore disabled commands silently instead of telling the member."]
+async fn commands_silent(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let silent = match args.message().to_lowercase().as_str() {
+        "on" | "true" | "yes" => true,
+        "off" | "false" | "no" => false,
+        _ => {
+            return Err(RusticError::BadArgument(t!(lang, "rules-silent-invalid")).into());
+        }
     };
 
-    let guild_id = msg.guild_id.unwrap().0 as i64;
-    query!("INSERT INTO guilds (guild_id, prefix) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET prefix = $2 WHERE guilds.guild_id = $1", guild_id, new_prefix)
-        .execute(&pool)
-        .await?;
+    commands_silent_response(ctx, lang, msg.guild_id.unwrap(), silent)
+        .await?
+        .send(ctx, msg)
+        .await
+}
 
-    msg.channel_id
-        .say(&ctx.http, format!("Updated prefix to: `{}`", new_prefix))
-        .await?;
+/// Check that an alias is usable and doesn't shadow a built-in command.
+fn validate_alias(lang: Lang, alias: &str) -> Result<(), RusticError> {
+    if alias.chars().count() > MAX_ALIAS_LENGTH
+        || !alias
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "alias-invalid",
+            max = MAX_ALIAS_LENGTH
+        )));
+    }
+
+    if is_command_name(alias) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "command-name-taken",
+            name = alias
+        )));
+    }
 
     Ok(())
 }
+
+async fn get_aliases(ctx: &Context, guild_id: GuildId) -> Arc<Aliases> {
+    let (pool, alias_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<AliasCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    alias_cache.get(&pool, guild_id).await
+}
+
+pub async fn alias_list_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+) -> Result<Response, RusticError> {
+    let aliases = get_aliases(ctx, guild_id).await;
+
+    if aliases.is_empty() {
+        return Ok(Response::Content(t!(lang, "alias-none")));
+    }
+
+    Ok(Response::Content(t!(
+        lang,
+        "alias-list",
+        aliases = aliases
+            .iter()
+            .map(|(alias, command)| format!("`{}` → `{}`", alias, command))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )))
+}
+
+pub async fn alias_add_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    alias: &str,
+    command: &str,
+) -> Result<Response, RusticError> {
+    let alias = alias.to_lowercase();
+    validate_alias(lang, &alias)?;
+
+    let command = match find_command(command) {
+        Some((group, command)) if group.name != "Owner" => command.options.names[0],
+        _ => {
+            return Err(RusticError::BadArgument(t!(
+                lang,
+                "alias-unknown-command",
+                command = command
+            )))
+        }
+    };
+
+    let aliases = get_aliases(ctx, guild_id).await;
+
+    if !aliases.contains_key(&alias) && aliases.len() >= MAX_ALIASES {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "alias-limit",
+            max = MAX_ALIASES
+        )));
+    }
+
+    let (pool, alias_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<AliasCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    aliases::set_alias(&pool, guild_id, &alias, command).await?;
+    alias_cache.invalidate(guild_id);
+
+    Ok(Response::Content(t!(
+        lang,
+        "alias-added",
+        alias = alias,
+        command = command
+    )))
+}
+
+pub async fn alias_remove_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    alias: &str,
+) -> Result<Response, RusticError> {
+    let alias = alias.to_lowercase();
+
+    let (pool, alias_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<AliasCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    if !aliases::remove_alias(&pool, guild_id, &alias).await? {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "alias-not-found",
+            alias = alias
+        )));
+    }
+    alias_cache.invalidate(guild_id);
+
+    Ok(Response::Content(t!(lang, "alias-removed", alias = alias)))
+}
+
+#[command]
+#[only_in(guilds)]
+#[sub_commands(alias_add, alias_remove)]
+#[description = "List the command aliases of this guild."]
+async fn alias(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    alias_list_response(ctx, lang, msg.guild_id.unwrap())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("add")]
+#[only_in(guilds)]
+#[num_args(2)]
+#[usage = "<alias> <command>"]
+#[example = "define urban"]
+#[description = "Add another name a command can be used with in this guild, replacing the alias if it exists."]
+async fn alias_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let alias = args.single::<String>()?;
+    let command = args.single::<String>()?;
+
+    alias_add_response(ctx, lang, msg.guild_id.unwrap(), &alias, &command)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("remove")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<alias>"]
+#[example = "define"]
+#[description = "Remove one of the aliases."]
+async fn alias_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    alias_remove_response(ctx, lang, msg.guild_id.unwrap(), args.message())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+/// The names of the supported languages, for the help of the language commands.
+pub fn language_names() -> String {
+    i18n::LANGUAGES
+        .iter()
+        .map(|(code, name, _)| format!("{} (`{}`)", name, code))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+pub async fn language_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    new_language: Option<&str>,
+) -> Result<Response, RusticError> {
+    let (pool, language_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<LanguageCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    let new_language = match new_language {
+        Some(new_language) => new_language,
+        None => {
+            let current = language_cache
+                .guild(&pool, guild_id)
+                .await
+                .unwrap_or_default();
+
+            return Ok(Response::Content(t!(
+                lang,
+                "language-current",
+                language = current.name(),
+                languages = language_names()
+            )));
+        }
+    };
+
+    let new_language = if new_language.eq_ignore_ascii_case("reset") {
+        None
+    } else {
+        Some(Lang::parse(new_language).ok_or_else(|| {
+            RusticError::BadArgument(t!(
+                lang,
+                "language-unknown",
+                language = new_language,
+                languages = language_names()
+            ))
+        })?)
+    };
+
+    i18n::set_guild_language(&pool, guild_id, new_language).await?;
+    language_cache.invalidate_guild(guild_id);
+
+    // Answer in the new language so the admin sees what members will get
+    let lang = new_language.unwrap_or_default();
+
+    Ok(Response::Content(t!(
+        lang,
+        "language-set",
+        language = lang.name()
+    )))
+}
+
+#[command("language")]
+#[only_in(guilds)]
+#[usage = "[language|reset]"]
+#[example = "español"]
+#[description = "Not providing the language will show the language of this guild. Providing a language name or code will make the bot answer in it, members can still pick their own with `mylanguage`."]
+async fn guild_language(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let new_language = if args.is_empty() {
+        None
+    } else {
+        Some(args.message())
+    };
+
+    language_response(ctx, lang, msg.guild_id.unwrap(), new_language)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+/// Show what embeds look like with a theme.
+fn theme_preview(lang: Lang, theme: &Theme, style: Style, title: String) -> Response {
+    let mut embed = theme.embed(style);
+    embed.title(title);
+    embed.description(t!(lang, "theme-preview"));
+    embed.fields(vec![
+        (
+            t!(lang, "theme-primary"),
+            format_colour(theme.primary),
+            true,
+        ),
+        (t!(lang, "theme-error"), format_colour(theme.error), true),
+        (
+            t!(lang, "theme-success"),
+            format_colour(theme.success),
+            true,
+        ),
+    ]);
+
+    Response::Embed(embed)
+}
+
+/// Change the theme of a guild and preview the result.
+async fn update_theme(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    title: String,
+    update: impl FnOnce(&mut Theme),
+) -> Result<Response, RusticError> {
+    let (pool, theme_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<ThemeCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    let mut new_theme = (*theme_cache.get(&pool, guild_id).await).clone();
+    update(&mut new_theme);
+
+    theme::set_theme(&pool, guild_id, &new_theme).await?;
+    theme_cache.invalidate(guild_id);
+
+    Ok(theme_preview(lang, &new_theme, Style::Success, title))
+}
+
+pub async fn theme_show_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+) -> Result<Response, RusticError> {
+    let current = theme::theme(ctx, Some(guild_id)).await;
+
+    Ok(theme_preview(
+        lang,
+        &current,
+        Style::Primary,
+        t!(lang, "theme-current"),
+    ))
+}
+
+pub async fn theme_colour_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    style: &str,
+    colour: &str,
+) -> Result<Response, RusticError> {
+    let style = match style.to_lowercase().as_str() {
+        "primary" => Style::Primary,
+        "error" => Style::Error,
+        "success" => Style::Success,
+        _ => {
+            return Err(RusticError::BadArgument(t!(
+                lang,
+                "theme-unknown-style",
+                style = style
+            )))
+        }
+    };
+
+    let colour = if colour.eq_ignore_ascii_case("reset") {
+        theme::DEFAULT_COLOUR
+    } else {
+        parse_colour(colour).ok_or_else(|| {
+            RusticError::BadArgument(t!(lang, "theme-invalid-colour", colour = colour))
+        })?
+    };
+
+    update_theme(
+        ctx,
+        lang,
+        guild_id,
+        t!(lang, "theme-updated"),
+        |theme| match style {
+            Style::Primary => theme.primary = colour,
+            Style::Error => theme.error = colour,
+            Style::Success => theme.success = colour,
+        },
+    )
+    .await
+}
+
+pub async fn theme_footer_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    footer: Option<&str>,
+) -> Result<Response, RusticError> {
+    let footer = footer.map(str::trim).filter(|footer| !footer.is_empty());
+
+    if footer.is_some_and(|footer| footer.chars().count() > MAX_FOOTER_LENGTH) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "theme-footer-too-long",
+            max = MAX_FOOTER_LENGTH
+        )));
+    }
+
+    update_theme(ctx, lang, guild_id, t!(lang, "theme-updated"), |theme| {
+        theme.footer = footer.map(str::to_string)
+    })
+    .await
+}
+
+pub async fn theme_author_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    name: Option<&str>,
+) -> Result<Response, RusticError> {
+    let name = name.map(str::trim).filter(|name| !name.is_empty());
+
+    if name.is_some_and(|name| name.chars().count() > MAX_AUTHOR_LENGTH) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "theme-author-too-long",
+            max = MAX_AUTHOR_LENGTH
+        )));
+    }
+
+    update_theme(ctx, lang, guild_id, t!(lang, "theme-updated"), |theme| {
+        theme.author_name = name.map(str::to_string)
+    })
+    .await
+}
+
+pub async fn theme_icon_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    icon: Option<&str>,
+) -> Result<Response, RusticError> {
+    let icon = icon.map(str::trim).filter(|icon| !icon.is_empty());
+
+    if let Some(icon) = icon {
+        if !icon.starts_with("https://") || icon.len() > MAX_ICON_URL_LENGTH || icon.contains(' ') {
+            return Err(RusticError::BadArgument(t!(lang, "theme-invalid-icon")));
+        }
+    }
+
+    let current = theme::theme(ctx, Some(guild_id)).await;
+    // The icon is only shown next to the author name
+    let title = if icon.is_some() && current.author_name.is_none() {
+        t!(lang, "theme-icon-needs-author")
+    } else {
+        t!(lang, "theme-updated")
+    };
+
+    update_theme(ctx, lang, guild_id, title, |theme| {
+        theme.author_icon = icon.map(str::to_string)
+    })
+    .await
+}
+
+pub async fn theme_reset_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+) -> Result<Response, RusticError> {
+    update_theme(ctx, lang, guild_id, t!(lang, "theme-reset"), |theme| {
+        *theme = Theme::default()
+    })
+    .await
+}
+
+/// Optional text arguments, where `none` also removes the setting.
+fn optional_text(args: &Args) -> Option<&str> {
+    if args.is_empty() || args.message().eq_ignore_ascii_case("none") {
+        None
+    } else {
+        Some(args.message())
+    }
+}
+
+#[command("theme")]
+#[only_in(guilds)]
+#[sub_commands(theme_colour, theme_footer, theme_author, theme_icon, theme_reset)]
+#[description = "Preview the colours, footer and author the bot's embeds use in this guild."]
+async fn guild_theme(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    theme_show_response(ctx, lang, msg.guild_id.unwrap())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("colour")]
+#[aliases("color")]
+#[only_in(guilds)]
+#[num_args(2)]
+#[usage = "<primary|error|success> <hex|name|reset>"]
+#[example = "primary #5865F2"]
+#[example = "error red"]
+#[description = "Change one of the embed colours, the primary colour is used by most embeds."]
+async fn theme_colour(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let style = args.single::<String>()?;
+    let colour = args.single::<String>()?;
+
+    theme_colour_response(ctx, lang, msg.guild_id.unwrap(), &style, &colour)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("footer")]
+#[only_in(guilds)]
+#[usage = "[text|none]"]
+#[example = "Powered by the Rustacean Station"]
+#[description = "Add a footer to the bot's embeds, not providing the text removes it. Embeds with their own footer keep it."]
+async fn theme_footer(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    theme_footer_response(ctx, lang, msg.guild_id.unwrap(), optional_text(&args))
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("author")]
+#[only_in(guilds)]
+#[usage = "[name|none]"]
+#[example = "Rustacean Station"]
+#[description = "Show a name at the top of the bot's embeds, not providing the name removes it."]
+async fn theme_author(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    theme_author_response(ctx, lang, msg.guild_id.unwrap(), optional_text(&args))
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("icon")]
+#[only_in(guilds)]
+#[usage = "[url|none]"]
+#[example = "https://example.com/logo.png"]
+#[description = "Show an icon next to the author name, not providing the url removes it."]
+async fn theme_icon(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    theme_icon_response(ctx, lang, msg.guild_id.unwrap(), optional_text(&args))
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("reset")]
+#[only_in(guilds)]
+#[description = "Reset the theme back to the default."]
+async fn theme_reset(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    theme_reset_response(ctx, lang, msg.guild_id.unwrap())
+        .await?
+        .send(ctx, msg)
+        .await
+}
@@ -24,12 +24,17 @@ use serenity::{
 };
 use std::borrow::Cow;
 
+use super::Response;
+use crate::error::RusticError;
+use crate::i18n::{language, t, Lang};
+use crate::theme::{theme, Style, Theme};
 use crate::ReqwestContainer;
 
-#[command]
-#[bucket = "basic"]
-#[description = "Find some cute cat pictures!"]
-async fn cat(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn cat_response(
+    ctx: &Context,
+    lang: Lang,
+    theme: &Theme,
+) -> Result<Response, RusticError> {
     let client = ctx
         .data
         .read()
@@ -40,70 +45,45 @@ async fn cat(ctx: &Context, msg: &Message) -> CommandResult {
     let request = client.get("https://shibe.online/api/cats").send().await?;
 
     if request.status() != 200 {
-        msg.channel_id
-            .say(&ctx.http, "Something went wrong, please try again later.")
-            .await?;
-
-        return Ok(());
+        return Err(RusticError::UpstreamUnavailable("shibe.online".to_string()));
     }
 
     let response: Vec<String> = request.json().await?;
 
-    msg.channel_id
-        .send_message(ctx, |message| {
-            message.embed(|embed| {
-                embed.title("Have a cute cat!");
-                embed.image(&response[0]);
-                embed.color(0xF05B4A);
-                embed
-            });
-            message
-        })
-        .await?;
+    let mut embed = theme.embed(Style::Primary);
+    embed.title(t!(lang, "cat-title"));
+    embed.image(&response[0]);
+
+    Ok(Response::Embed(embed))
+}
+
+#[command]
+#[bucket = "basic"]
+#[description = "Find some cute cat pictures!"]
+async fn cat(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let theme = theme(ctx, msg.guild_id).await;
 
-    Ok(())
+    cat_response(ctx, lang, &theme).await?.send(ctx, msg).await
+}
+
+pub fn eightball_response(lang: Lang) -> Response {
+    let answer = format!("eightball-{}", rand::thread_rng().gen_range(1..=20));
+
+    Response::Reply(t!(lang, "eightball", answer = t!(lang, &answer)))
 }
 
 #[command]
 #[min_args(1)]
 #[bucket = "basic"]
 #[usage = "<question>"]
+#[example = "Will it rain tomorrow?"]
 #[aliases("8ball", "8b")]
 #[description = "Ask a question to the magic 8ball"]
 async fn eightball(ctx: &Context, msg: &Message) -> CommandResult {
-    let responses: [&str; 20] = [
-        "It is certain.",
-        "It is decidedly so.",
-        "Without a doubt.",
-        "Yes definitely.",
-        "You may rely on it.",
-        "As I see it, yes.",
-        "Most likely.",
-        "Outlook good.",
-        "Yes.",
-        "Signs point to yes.",
-        "Reply hazy, try again.",
-        "Ask again later.",
-        "Better not tell you now.",
-        "Cannot predict now.",
-        "Concentrate and ask again.",
-        "Don't count on it.",
-        "My reply is no.",
-        "My sources say no.",
-        "Outlook not so good.",
-        "Very doubtful.",
-    ];
-
-    msg.reply(
-        &ctx.http,
-        &format!(
-            ":8ball: **8ball:** {}",
-            responses[rand::thread_rng().gen_range(0..=20)]
-        ),
-    )
-    .await?;
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
 
-    Ok(())
+    eightball_response(lang).send(ctx, msg).await
 }
 
 #[derive(Debug, Deserialize)]
@@ -119,10 +99,9 @@ struct Definition {
     author: String,
     thumbs_up: u32,
     thumbs_down: u32,
-    written_on: String,
 }
 
-fn cleanup_definition(definition: &str) -> Cow<str> {
+fn cleanup_definition(definition: &str) -> Cow<'_, str> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"(\[(?P<w>.+?)\])").unwrap();
     }
@@ -135,20 +114,16 @@ fn cleanup_definition(definition: &str) -> Cow<str> {
     })
 }
 
-#[command]
-#[bucket = "basic"]
-#[usage = "<word>"]
-#[description = "Searches urban dictionary."]
-async fn urban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    if args.rest().is_empty() {
-        msg.channel_id
-            .say(&ctx.http, "Give me a word to look up for.")
-            .await?;
-        return Ok(());
+pub async fn urban_response(
+    ctx: &Context,
+    lang: Lang,
+    theme: &Theme,
+    word: &str,
+) -> Result<Response, RusticError> {
+    if word.is_empty() {
+        return Err(RusticError::BadArgument(t!(lang, "urban-no-word")));
     }
 
-    let word = args.rest();
-
     let client = ctx
         .data
         .read()
@@ -164,45 +139,53 @@ async fn urban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         .await?;
 
     if request.status() != 200 {
-        msg.channel_id
-            .say(&ctx.http, "Something went wrong, please try again later.")
-            .await?;
-
-        return Ok(());
+        return Err(RusticError::UpstreamUnavailable(
+            "Urban Dictionary".to_string(),
+        ));
     }
 
     let response: UrbanResponse = request.json().await?;
 
     if response.list.is_empty() {
-        msg.channel_id
-            .say(&ctx.http, "No results found, sorry.")
-            .await?;
-        return Ok(());
+        return Ok(Response::Content(t!(lang, "urban-no-results")));
     }
 
-    let definition = response.list.get(0).unwrap();
-
-    msg.channel_id
-        .send_message(ctx, |message| {
-            message.embed(|embed| {
-                embed.title(&definition.word);
-                embed.url(&definition.permalink);
-                embed.description(cleanup_definition(&definition.definition));
-                embed.field(
-                    "Votes",
-                    format!(
-                        ":thumbsup: {} :thumbsdown: {}",
-                        &definition.thumbs_up, &definition.thumbs_down
-                    ),
-                    false,
-                );
-                embed.footer(|f| f.text(format!("by {}", &definition.author)));
-                embed.color(0xF05B4A);
-                embed
-            });
-            message
-        })
-        .await?;
+    let definition = response.list.first().unwrap();
+
+    let mut embed = theme.embed(Style::Primary);
+    embed.title(&definition.word);
+    embed.url(&definition.permalink);
+    embed.description(cleanup_definition(&definition.definition));
+    embed.field(
+        t!(lang, "urban-votes"),
+        format!(
+            ":thumbsup: {} :thumbsdown: {}",
+            &definition.thumbs_up, &definition.thumbs_down
+        ),
+        false,
+    );
+    embed.footer(|f| {
+        f.text(t!(
+            lang,
+            "urban-author",
+            author = definition.author.as_str()
+        ))
+    });
+
+    Ok(Response::Embed(embed))
+}
 
-    Ok(())
+#[command]
+#[bucket = "basic"]
+#[usage = "<word>"]
+#[example = "rustacean"]
+#[description = "Searches urban dictionary."]
+async fn urban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let theme = theme(ctx, msg.guild_id).await;
+
+    urban_response(ctx, lang, &theme, args.rest())
+        .await?
+        .send(ctx, msg)
+        .await
 }
@@ -13,138 +13,421 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::ReqwestContainer;
-use image::ImageFormat;
-use serenity::utils::ArgumentConvert;
+use super::Response;
+use crate::{
+    error::RusticError,
+    i18n::{language, t, Lang},
+    pipeline::{self, Blur, Grayscale, ImageFilter},
+    ImageWorkersContainer, USER_AGENT,
+};
+use reqwest::{header::CONTENT_TYPE, redirect::Policy, Client, Url};
 use serenity::{
     framework::standard::{macros::command, Args, CommandResult},
-    http::AttachmentType,
-    model::prelude::{Member, Message},
+    model::prelude::{ChannelId, GuildId, Member, Message, User},
     prelude::Context,
+    utils::{parse_emoji, ArgumentConvert},
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
 };
-use std::borrow::Cow;
+use tokio::net::lookup_host;
 
-#[command]
-#[usage = "[member]"]
-#[bucket = "basic"]
-#[description = "Adds a grayscale filter to your avatar or the mentioned member."]
-#[aliases("gray", "grey", "greyscale")]
-async fn grayscale(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let url = {
-        if args.message().is_empty() {
-            msg.author
-                .face()
-                .replace("webp", "png")
-                .replace("gif", "png")
-        } else {
-            let m = <Member as ArgumentConvert>::convert(
-                ctx,
-                msg.guild_id,
-                Some(msg.channel_id),
-                args.message(),
-            )
-            .await?;
-            args.advance();
-            m.face().replace("webp", "png").replace("gif", "png")
+/// Largest image the commands download, in bytes.
+const MAX_IMAGE_SIZE: usize = 8 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+/// The size avatars and emojis are requested in from Discord.
+const CDN_SIZE: u32 = 512;
+const CDN_HOSTS: &[&str] = &["cdn.discordapp.com", "media.discordapp.net"];
+/// CDN paths which are resized and converted by Discord, attachments aren't.
+const CDN_ASSETS: &[&str] = &["/avatars/", "/emojis/", "/icons/", "/embed/avatars/"];
+
+/// Where an image command can find its image.
+pub struct ImageQuery<'a> {
+    pub author: &'a User,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    /// The message of a prefix command, for its attachments and the message it replies to.
+    pub message: Option<&'a Message>,
+    /// A link, a custom emoji or a member.
+    pub argument: &'a str,
+}
+
+/// Ask the Discord CDN for a PNG in a fixed size, as WebP can't always be decoded and the
+/// size given by the user or by Discord could be anything up to 4096. Animated avatars and
+/// emojis stay GIFs. Other links are kept.
+fn cdn_url(link: &str) -> String {
+    let mut url = match Url::parse(link) {
+        Ok(url) => url,
+        Err(_) => return link.to_string(),
+    };
+
+    let is_asset = url.host_str().is_some_and(|host| CDN_HOSTS.contains(&host))
+        && CDN_ASSETS.iter().any(|path| url.path().starts_with(path));
+    if !is_asset {
+        return link.to_string();
+    }
+
+    let path = match url.path().rsplit_once('.') {
+        Some((stem, extension)) if extension.eq_ignore_ascii_case("gif") => {
+            format!("{}.gif", stem)
         }
+        Some((stem, _)) => format!("{}.png", stem),
+        None => format!("{}.png", url.path()),
     };
+    url.set_path(&path);
 
-    let client = ctx
-        .data
-        .read()
-        .await
-        .get::<ReqwestContainer>()
-        .cloned()
-        .unwrap();
-    let avatar_bytes = client
-        .get(&url)
-        .send()
-        .await?
-        .bytes()
-        .await?
-        .into_iter()
-        .collect::<Vec<u8>>();
+    let query = url
+        .query_pairs()
+        .filter(|(key, _)| key != "size")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<(String, String)>>();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("size", &CDN_SIZE.to_string());
 
-    let mut bytes = Vec::new();
-    let buffer = image::load_from_memory_with_format(&avatar_bytes, ImageFormat::Png)?.into_rgba8();
+    url.into()
+}
 
-    image::DynamicImage::ImageRgba8(buffer)
-        .grayscale()
-        .write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+pub fn avatar(user: &User) -> String {
+    cdn_url(&user.face())
+}
 
-    let attachment = AttachmentType::Bytes {
-        data: Cow::from(bytes),
-        filename: String::from("grayscale.png"),
-    };
+fn is_image(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+
+    path.rsplit_once('.')
+        .is_some_and(|(_, extension)| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
 
-    msg.channel_id
-        .send_message(ctx, |message| {
-            message.add_file(attachment);
-            message
+fn attached_image(message: &Message) -> Option<String> {
+    message
+        .attachments
+        .iter()
+        .find(|attachment| is_image(&attachment.filename))
+        .map(|attachment| attachment.url.clone())
+}
+
+/// An attached image, or the image of an embed like a link preview.
+fn message_image(message: &Message) -> Option<String> {
+    attached_image(message).or_else(|| {
+        message.embeds.iter().find_map(|embed| {
+            embed
+                .image
+                .as_ref()
+                .map(|image| image.url.clone())
+                .or_else(|| {
+                    embed
+                        .thumbnail
+                        .as_ref()
+                        .map(|thumbnail| thumbnail.url.clone())
+                })
+        })
+    })
+}
+
+/// The newest image in the cached messages of a channel.
+async fn last_image(
+    ctx: &Context,
+    channel_id: ChannelId,
+    skip: Option<&Message>,
+) -> Option<String> {
+    let mut messages = ctx
+        .cache
+        .channel_messages_field(channel_id, |messages| {
+            messages
+                .filter(|message| skip.is_none_or(|skip| skip.id != message.id))
+                .cloned()
+                .collect::<Vec<Message>>()
         })
         .await?;
+    messages.sort_by_key(|message| std::cmp::Reverse(message.id));
 
-    Ok(())
+    messages.iter().find_map(message_image)
 }
 
-#[command]
-#[usage = "[member]"]
-#[bucket = "basic"]
-#[description = "Blur your avatar or the mentioned member."]
-async fn blur(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let url = {
-        if args.message().is_empty() {
-            msg.author
-                .face()
-                .replace("webp", "png")
-                .replace("gif", "png")
-        } else {
-            let m = <Member as ArgumentConvert>::convert(
-                ctx,
-                msg.guild_id,
-                Some(msg.channel_id),
-                args.message(),
-            )
-            .await?;
-            args.advance();
-            m.face().replace("webp", "png").replace("gif", "png")
+/// Find the image to use, in order: an attachment, an image in the replied to message, a
+/// link, a custom emoji, a member's avatar, the last image in the channel and the author's
+/// avatar.
+pub async fn resolve_image(ctx: &Context, query: ImageQuery<'_>) -> Result<String, RusticError> {
+    if let Some(message) = query.message {
+        if let Some(url) = attached_image(message) {
+            return Ok(url);
         }
-    };
 
-    let client = ctx
-        .data
-        .read()
+        if let Some(url) = message
+            .referenced_message
+            .as_deref()
+            .and_then(message_image)
+        {
+            return Ok(url);
+        }
+    }
+
+    let argument = query.argument.trim();
+
+    if !argument.is_empty() {
+        // Links in angle brackets don't get an embed
+        let link = argument.trim_start_matches('<').trim_end_matches('>');
+        if link.starts_with("https://") || link.starts_with("http://") {
+            return Ok(cdn_url(link));
+        }
+
+        if let Some(emoji) = parse_emoji(argument) {
+            return Ok(cdn_url(&format!(
+                "https://cdn.discordapp.com/emojis/{}.{}",
+                emoji.id.0,
+                if emoji.animated { "gif" } else { "png" }
+            )));
+        }
+
+        let member = <Member as ArgumentConvert>::convert(
+            ctx,
+            query.guild_id,
+            Some(query.channel_id),
+            argument,
+        )
+        .await
+        .map_err(|_| RusticError::MemberNotFound(argument.to_string()))?;
+
+        return Ok(avatar(&member.user));
+    }
+
+    match last_image(ctx, query.channel_id, query.message).await {
+        Some(url) => Ok(url),
+        None => Ok(avatar(query.author)),
+    }
+}
+
+/// Whether an address is on the internet, and not the bot's own machine or network.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // The shared address space of carrier-grade NATs, 100.64.0.0/10
+                || (first == 100 && (64..128).contains(&second))
+                || first == 0
+                || first >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local, fc00::/7, and link-local, fe80::/10
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolve the host of an https link, refusing it when any of its addresses isn't public.
+async fn public_address(url: &Url) -> Option<(String, SocketAddr)> {
+    if url.scheme() != "https" {
+        return None;
+    }
+
+    let host = url.host_str()?.to_string();
+    let addresses = lookup_host((host.as_str(), url.port_or_known_default()?))
         .await
-        .get::<ReqwestContainer>()
-        .cloned()
-        .unwrap();
-    let avatar_bytes = client
-        .get(&url)
+        .ok()?
+        .collect::<Vec<SocketAddr>>();
+
+    if addresses.is_empty() || !addresses.iter().all(|address| is_public(address.ip())) {
+        return None;
+    }
+
+    Some((host, addresses[0]))
+}
+
+/// Download an image, refusing anything which isn't an image or is too large. Only public
+/// https links are downloaded, and redirects aren't followed since they could lead anywhere.
+async fn fetch_image(lang: Lang, url: &str) -> Result<Vec<u8>, RusticError> {
+    let download_failed = || RusticError::BadArgument(t!(lang, "image-download-failed"));
+    let not_allowed = || RusticError::BadArgument(t!(lang, "image-link-not-allowed"));
+
+    let url = Url::parse(url).map_err(|_| not_allowed())?;
+    let (host, address) = public_address(&url).await.ok_or_else(not_allowed)?;
+
+    // Connect to the address that was checked, the host could resolve differently next time
+    let client = Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(Policy::none())
+        .resolve(&host, address)
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(RusticError::internal)?;
+    let too_large = || {
+        RusticError::BadArgument(t!(
+            lang,
+            "image-too-large",
+            max = MAX_IMAGE_SIZE / 1024 / 1024
+        ))
+    };
+
+    let mut response = client
+        .get(url)
         .send()
-        .await?
-        .bytes()
-        .await?
-        .into_iter()
-        .collect::<Vec<u8>>();
+        .await
+        .map_err(|_| download_failed())?;
+    // Redirects aren't errors, so check for a success instead of using `error_for_status`
+    if !response.status().is_success() {
+        return Err(download_failed());
+    }
 
+    let is_image = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("image/"));
+    if !is_image {
+        return Err(RusticError::BadArgument(t!(lang, "image-not-an-image")));
+    }
+
+    if response.content_length().unwrap_or(0) > MAX_IMAGE_SIZE as u64 {
+        return Err(too_large());
+    }
+
+    // The length header is optional, so check the size while downloading too
     let mut bytes = Vec::new();
-    let buffer = image::load_from_memory_with_format(&avatar_bytes, ImageFormat::Png)?.into_rgba8();
+    while let Some(chunk) = response.chunk().await.map_err(|_| download_failed())? {
+        if bytes.len() + chunk.len() > MAX_IMAGE_SIZE {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
 
-    image::DynamicImage::ImageRgba8(buffer)
-        .blur(5.0)
-        .write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+    Ok(bytes)
+}
 
-    let attachment = AttachmentType::Bytes {
-        data: Cow::from(bytes),
-        filename: String::from("blur.png"),
+/// Run a filter on an image: download, decode, apply it and upload the result.
+pub async fn filter_response(
+    ctx: &Context,
+    lang: Lang,
+    filter: &'static dyn ImageFilter,
+    url: &str,
+) -> Result<Response, RusticError> {
+    let image_bytes = fetch_image(lang, url).await?;
+    let workers = {
+        let data = ctx.data.read().await;
+        data.get::<ImageWorkersContainer>().unwrap().clone()
     };
 
-    msg.channel_id
-        .send_message(ctx, |message| {
-            message.add_file(attachment);
-            message
-        })
+    let options = workers.options;
+    let output = workers
+        .run(move || pipeline::process(filter, &image_bytes, options))
         .await?;
 
-    Ok(())
+    Ok(Response::Attachment {
+        data: output.data,
+        filename: format!("{}.{}", filter.name(), output.extension),
+        content: output.flattened.then(|| {
+            t!(
+                lang,
+                "image-gif-flattened",
+                frames = options.max_frames,
+                max = pipeline::MAX_UPLOAD_SIZE / 1024 / 1024
+            )
+        }),
+    })
+}
+
+/// The prefix command of a filter.
+async fn run_filter(
+    ctx: &Context,
+    msg: &Message,
+    args: Args,
+    filter: &'static dyn ImageFilter,
+) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let url = resolve_image(
+        ctx,
+        ImageQuery {
+            author: &msg.author,
+            guild_id: msg.guild_id,
+            channel_id: msg.channel_id,
+            message: Some(msg),
+            argument: args.rest(),
+        },
+    )
+    .await?;
+
+    filter_response(ctx, lang, filter, &url)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command]
+#[usage = "[member|emoji|link]"]
+#[example = "@MrArkon"]
+#[bucket = "image"]
+#[description = "Blur an image. Uses an attached image, the image you reply to, a link, a custom emoji or a member's avatar, otherwise the last image in the channel or your avatar."]
+async fn blur(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_filter(ctx, msg, args, &Blur).await
+}
+
+#[command]
+#[usage = "[member|emoji|link]"]
+#[example = "@MrArkon"]
+#[bucket = "image"]
+#[description = "Adds a grayscale filter to an image. Uses an attached image, the image you reply to, a link, a custom emoji or a member's avatar, otherwise the last image in the channel or your avatar."]
+#[aliases("gray", "grey", "greyscale")]
+async fn grayscale(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_filter(ctx, msg, args, &Grayscale).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn refuses_private_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!public(ip), "{} should be refused", ip);
+        }
+    }
+
+    #[test]
+    fn allows_public_addresses() {
+        for ip in ["162.159.130.233", "8.8.8.8", "2606:4700::6810:84e5"] {
+            assert!(public(ip), "{} should be allowed", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn only_downloads_https_links() {
+        let url = Url::parse("http://cdn.discordapp.com/avatars/1/a.png").unwrap();
+        assert!(public_address(&url).await.is_none());
+    }
 }
@@ -15,28 +15,29 @@
 
 use serenity::{
     client::bridge::gateway::ShardId,
-    framework::standard::{macros::command, CommandResult},
-    model::prelude::Message,
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::{Message, UserId},
     prelude::Context,
 };
 use simple_process_stats::ProcessStats;
 
-use crate::ShardManagerContainer;
-
-#[command]
-#[description = "Check if the bot is working."]
-#[bucket = "basic"]
-async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
+use super::Response;
+use crate::commands::config::language_names;
+use crate::error::RusticError;
+use crate::i18n::{self, language, t, Lang};
+use crate::theme::{theme, Style, Theme};
+use crate::{LanguageCacheContainer, PgPoolContainer, PrefixCacheContainer, ShardManagerContainer};
+
+pub async fn ping_response(
+    ctx: &Context,
+    lang: Lang,
+    theme: &Theme,
+) -> Result<Response, RusticError> {
     let data_read = ctx.data.read().await;
 
     let shard_manager = match data_read.get::<ShardManagerContainer>() {
         Some(s) => s,
-        None => {
-            msg.reply(ctx, "Something went wrong, Please try again later.")
-                .await?;
-
-            return Ok(());
-        }
+        None => return Ok(Response::Reply(t!(lang, "ping-failed"))),
     };
 
     let manager = shard_manager.lock().await;
@@ -47,40 +48,42 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
             Some(ms) => format!("{}ms", ms.as_millis()),
             _ => "?ms".to_string(),
         },
-        None => {
-            msg.reply(ctx, "Something went wrong, Please try again.")
-                .await?;
-
-            return Ok(());
-        }
+        None => return Ok(Response::Reply(t!(lang, "ping-failed"))),
     };
 
     let icon_url = ctx.cache.current_user().await.face();
 
-    msg.channel_id
-        .send_message(ctx, |message| {
-            message.embed(|embed| {
-                embed.author(|author| {
-                    author.name("Pong!");
-                    author.icon_url(icon_url);
-                    author
-                });
-                embed.description(&format!("**Shard {}**: {}", ctx.shard_id + 1, latency));
-                embed.color(0xF05B4A);
-                embed
-            });
-            message
-        })
-        .await?;
-
-    Ok(())
+    let mut embed = theme.embed(Style::Primary);
+    embed.author(|author| {
+        author.name(t!(lang, "ping-title"));
+        author.icon_url(icon_url);
+        author
+    });
+    embed.description(t!(
+        lang,
+        "ping-latency",
+        shard = ctx.shard_id + 1,
+        latency = latency
+    ));
+
+    Ok(Response::Embed(embed))
 }
 
 #[command]
+#[description = "Check if the bot is working."]
 #[bucket = "basic"]
-#[aliases("statistics", "stats")]
-#[description = "Tells you information about the bot itself."]
-async fn about(ctx: &Context, msg: &Message) -> CommandResult {
+async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let theme = theme(ctx, msg.guild_id).await;
+
+    ping_response(ctx, lang, &theme).await?.send(ctx, msg).await
+}
+
+pub async fn about_response(
+    ctx: &Context,
+    lang: Lang,
+    theme: &Theme,
+) -> Result<Response, RusticError> {
     let discriminator = ctx.cache.current_user().await.discriminator;
     let icon_url = ctx.cache.current_user().await.face();
 
@@ -88,41 +91,137 @@ async fn about(ctx: &Context, msg: &Message) -> CommandResult {
     let total_shards = ctx.cache.shard_count().await;
     let total_users = ctx.cache.user_count().await;
 
-    let process_stats = ProcessStats::get()
+    let prefix_cache = ctx
+        .data
+        .read()
+        .await
+        .get::<PrefixCacheContainer>()
+        .cloned()
+        .unwrap();
+
+    let process_stats = ProcessStats::get().await.map_err(RusticError::internal)?;
+
+    let mut embed = theme.embed(Style::Primary);
+    embed.author(|author| {
+        author.name(format!("Rustic#{}", discriminator));
+        author.icon_url(icon_url);
+        author
+    });
+    embed.description(t!(lang, "about-description"));
+    embed.fields(vec![
+        (t!(lang, "about-guilds"), total_guilds.to_string(), true),
+        (t!(lang, "about-users"), total_users.to_string(), true),
+        (
+            t!(lang, "about-shard"),
+            format!("{}/{}", ctx.shard_id + 1, total_shards),
+            true,
+        ),
+        (
+            t!(lang, "about-memory"),
+            format!("{} MB", process_stats.memory_usage_bytes / 8000000),
+            true,
+        ),
+        (
+            t!(lang, "about-prefix-cache"),
+            t!(
+                lang,
+                "about-prefix-cache-stats",
+                hits = prefix_cache.hits(),
+                misses = prefix_cache.misses()
+            ),
+            true,
+        ),
+    ]);
+    embed.footer(|f| f.text(t!(lang, "about-footer")));
+
+    Ok(Response::Embed(embed))
+}
+
+#[command]
+#[bucket = "basic"]
+#[aliases("statistics", "stats")]
+#[description = "Tells you information about the bot itself."]
+async fn about(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let theme = theme(ctx, msg.guild_id).await;
+
+    about_response(ctx, lang, &theme)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+pub async fn mylanguage_response(
+    ctx: &Context,
+    lang: Lang,
+    user_id: UserId,
+    new_language: Option<&str>,
+) -> Result<Response, RusticError> {
+    let (pool, language_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<LanguageCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    let new_language = match new_language {
+        Some(new_language) => new_language,
+        None => {
+            return Ok(Response::Content(
+                match language_cache.user(&pool, user_id).await {
+                    Some(current) => t!(
+                        lang,
+                        "mylanguage-current",
+                        language = current.name(),
+                        languages = language_names()
+                    ),
+                    None => t!(lang, "mylanguage-none", languages = language_names()),
+                },
+            ))
+        }
+    };
+
+    let new_language = if new_language.eq_ignore_ascii_case("reset") {
+        None
+    } else {
+        Some(Lang::parse(new_language).ok_or_else(|| {
+            RusticError::BadArgument(t!(
+                lang,
+                "language-unknown",
+                language = new_language,
+                languages = language_names()
+            ))
+        })?)
+    };
+
+    i18n::set_user_language(&pool, user_id, new_language).await?;
+    language_cache.invalidate_user(user_id);
+
+    Ok(Response::Content(match new_language {
+        Some(new_language) => t!(
+            new_language,
+            "mylanguage-set",
+            language = new_language.name()
+        ),
+        None => t!(lang, "mylanguage-reset"),
+    }))
+}
+
+#[command]
+#[usage = "[language|reset]"]
+#[example = "português"]
+#[description = "Not providing the language will show the language the bot answers you in. Providing a language name or code will make the bot always answer you in it, `reset` goes back to the language of each server."]
+async fn mylanguage(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let new_language = if args.is_empty() {
+        None
+    } else {
+        Some(args.message())
+    };
+
+    mylanguage_response(ctx, lang, msg.author.id, new_language)
+        .await?
+        .send(ctx, msg)
         .await
-        .expect("Couldn't get statistics for the running process");
-
-    msg.channel_id
-        .send_message(ctx, |message| {
-            message.embed(|embed| {
-                embed.author(|author| {
-                    author.name(format!("Rustic#{}", discriminator));
-                    author.icon_url(icon_url);
-                    author
-                });
-                embed.description(
-                    "
-                    Rustic is an open source multi-purpose bot packed with features
-                    You can find my source code on [github](https://github.com/MrArkon/Rustic), Developed by [MrArkon](https://mrarkon.github.io)
-                ",
-                );
-                embed.fields(vec![
-                    ("Guilds", total_guilds.to_string(), true),
-                    ("Users", total_users.to_string(), true),
-                    ("Shard", format!("{}/{}", ctx.shard_id + 1, total_shards), true),
-                    (
-                        "Memory Usage",
-                        format!("{} MB", process_stats.memory_usage_bytes / 8000000),
-                        true,
-                    )
-                ]);
-                embed.color(0xF05B4A);
-                embed.footer(|f| f.text("Written with Rust & Serenity-rs"));
-                embed
-            });
-            message
-        })
-        .await?;
-
-    Ok(())
 }
@@ -17,3 +17,97 @@ pub mod config;
 pub mod fun;
 pub mod image;
 pub mod misc;
+pub mod owner;
+pub mod reminders;
+pub mod slash;
+pub mod tags;
+pub mod time;
+
+use serenity::{
+    builder::CreateEmbed,
+    framework::standard::CommandResult,
+    http::AttachmentType,
+    model::{interactions::application_command::ApplicationCommandInteraction, prelude::Message},
+    prelude::Context,
+};
+use std::borrow::Cow;
+
+/// The output of a command, shared by the prefix and the slash command versions
+/// so both of them always respond the same way.
+pub enum Response {
+    Content(String),
+    Reply(String),
+    Embed(CreateEmbed),
+    Attachment {
+        data: Vec<u8>,
+        filename: String,
+        content: Option<String>,
+    },
+}
+
+impl Response {
+    /// Send the response to the channel the prefix command was invoked in.
+    pub async fn send(self, ctx: &Context, msg: &Message) -> CommandResult {
+        match self {
+            Response::Content(content) => {
+                msg.channel_id.say(&ctx.http, content).await?;
+            }
+            Response::Reply(content) => {
+                msg.reply(&ctx.http, content).await?;
+            }
+            Response::Embed(embed) => {
+                msg.channel_id
+                    .send_message(ctx, |message| message.set_embed(embed))
+                    .await?;
+            }
+            Response::Attachment {
+                data,
+                filename,
+                content,
+            } => {
+                msg.channel_id
+                    .send_message(ctx, |message| {
+                        if let Some(content) = content {
+                            message.content(content);
+                        }
+                        message.add_file(AttachmentType::Bytes {
+                            data: Cow::from(data),
+                            filename,
+                        })
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send the response as a followup to a deferred slash command.
+    pub async fn followup(
+        self,
+        ctx: &Context,
+        interaction: &ApplicationCommandInteraction,
+    ) -> CommandResult {
+        interaction
+            .create_followup_message(&ctx.http, |followup| match self {
+                Response::Content(content) | Response::Reply(content) => followup.content(content),
+                Response::Embed(embed) => followup.add_embed(embed),
+                Response::Attachment {
+                    data,
+                    filename,
+                    content,
+                } => {
+                    if let Some(content) = content {
+                        followup.content(content);
+                    }
+                    followup.add_file(AttachmentType::Bytes {
+                        data: Cow::from(data),
+                        filename,
+                    })
+                }
+            })
+            .await?;
+
+        Ok(())
+    }
+}
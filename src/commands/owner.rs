@@ -0,0 +1,756 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    blocklist::{self, Entry, Target},
+    error::RusticError,
+    reload::reload,
+    settings::{ActivityKind, ActivitySettings, MAX_ACTIVITY_LENGTH},
+    theme::{Style, Theme},
+    utils::parse_duration,
+    FrameworkContainer, PgPoolContainer, PresenceContainer, ReqwestContainer,
+    ShardManagerContainer,
+};
+use chrono::{DateTime, Utc};
+use image::ImageFormat;
+use serenity::{
+    client::bridge::gateway::ShardId,
+    framework::standard::{macros::command, ArgError, Args, CommandResult},
+    model::prelude::{GuildId, Message, UserId},
+    prelude::Context,
+};
+use sqlx::{postgres::PgRow, Column, Row, TypeInfo, ValueRef};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+const GUILDS_PER_PAGE: usize = 15;
+const MAX_SQL_ROWS: usize = 25;
+/// Milliseconds a query from the `sql` command may run for.
+const SQL_TIMEOUT: u32 = 5000;
+
+#[command]
+#[owners_only]
+#[description = "Disconnect every shard and stop the bot."]
+async fn shutdown(ctx: &Context, msg: &Message) -> CommandResult {
+    let shard_manager = ctx
+        .data
+        .read()
+        .await
+        .get::<ShardManagerContainer>()
+        .unwrap()
+        .clone();
+
+    msg.channel_id.say(&ctx.http, "Shutting down...").await?;
+    info!("Shutting down, requested by {}", msg.author.tag());
+
+    shard_manager.lock().await.shutdown_all().await;
+
+    Ok(())
+}
+
+#[command("restart-shard")]
+#[owners_only]
+#[num_args(1)]
+#[usage = "<shard id>"]
+#[example = "0"]
+#[description = "Reconnect a single shard."]
+async fn restart_shard(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let shard_id = args
+        .single::<u64>()
+        .map_err(|_| RusticError::BadArgument("Give me the ID of a shard.".to_string()))?;
+
+    let shard_manager = ctx
+        .data
+        .read()
+        .await
+        .get::<ShardManagerContainer>()
+        .unwrap()
+        .clone();
+    let mut shard_manager = shard_manager.lock().await;
+
+    if !shard_manager
+        .shards_instantiated()
+        .await
+        .contains(&ShardId(shard_id))
+    {
+        return Err(RusticError::BadArgument(format!("Shard {} isn't running.", shard_id)).into());
+    }
+
+    msg.channel_id
+        .say(&ctx.http, format!("Restarting shard {}...", shard_id))
+        .await?;
+    info!(
+        "Restarting shard {}, requested by {}",
+        shard_id,
+        msg.author.tag()
+    );
+
+    shard_manager.restart(ShardId(shard_id)).await;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[max_args(1)]
+#[usage = "[page]"]
+#[example = "2"]
+#[description = "List the guilds the bot is in, largest first."]
+async fn guilds(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let page = if args.is_empty() {
+        1
+    } else {
+        args.single::<usize>()
+            .map_err(|_| RusticError::BadArgument("Give me a page number.".to_string()))?
+    };
+
+    let mut guilds = Vec::new();
+    for guild_id in ctx.cache.guilds().await {
+        if let Some((name, member_count)) = ctx
+            .cache
+            .guild_field(guild_id, |guild| (guild.name.clone(), guild.member_count))
+            .await
+        {
+            guilds.push((guild_id, name, member_count));
+        }
+    }
+    guilds.sort_by_key(|(_, _, member_count)| std::cmp::Reverse(*member_count));
+
+    let pages = guilds.len().div_ceil(GUILDS_PER_PAGE).max(1);
+    if page == 0 || page > pages {
+        return Err(RusticError::BadArgument(format!(
+            "There are only {} page(s) of guilds.",
+            pages
+        ))
+        .into());
+    }
+
+    let listing = guilds
+        .iter()
+        .skip((page - 1) * GUILDS_PER_PAGE)
+        .take(GUILDS_PER_PAGE)
+        .map(|(guild_id, name, member_count)| {
+            format!("`{}` **{}** - {} members", guild_id.0, name, member_count)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let mut embed = Theme::default().embed(Style::Primary);
+    embed.title(format!("Guilds ({})", guilds.len()));
+    embed.description(listing);
+    embed.footer(|f| f.text(format!("Page {}/{}", page, pages)));
+
+    msg.channel_id
+        .send_message(ctx, |message| message.set_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[num_args(1)]
+#[usage = "<guild id>"]
+#[example = "881500906394578974"]
+#[description = "Make the bot leave a guild."]
+async fn leave(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = args
+        .single::<u64>()
+        .map(GuildId)
+        .map_err(|_| RusticError::BadArgument("Give me the ID of a guild.".to_string()))?;
+
+    let name = guild_id.name(ctx).await.ok_or_else(|| {
+        RusticError::BadArgument(format!("I'm not in a guild with the ID `{}`.", guild_id.0))
+    })?;
+
+    guild_id.leave(&ctx.http).await?;
+    info!(
+        "Left guild {} ({}), requested by {}",
+        name,
+        guild_id.0,
+        msg.author.tag()
+    );
+
+    msg.channel_id
+        .say(&ctx.http, format!("Left **{}**.", name))
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[max_args(1)]
+#[usage = "[image url]"]
+#[example = "https://example.com/avatar.png"]
+#[description = "Change the avatar of the bot to the attached image or the image at the url."]
+async fn setavatar(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let bytes = match msg.attachments.first() {
+        Some(attachment) => attachment.download().await?,
+        None if !args.is_empty() => {
+            let client = ctx
+                .data
+                .read()
+                .await
+                .get::<ReqwestContainer>()
+                .cloned()
+                .unwrap();
+            client
+                .get(args.message())
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec()
+        }
+        None => {
+            return Err(RusticError::BadArgument(
+                "Attach an image or give me the url of one.".to_string(),
+            )
+            .into())
+        }
+    };
+
+    let mime = match image::guess_format(&bytes) {
+        Ok(ImageFormat::Png) => "image/png",
+        Ok(ImageFormat::Jpeg) => "image/jpeg",
+        Ok(ImageFormat::Gif) => "image/gif",
+        _ => {
+            return Err(RusticError::BadArgument(
+                "The avatar has to be a PNG, JPEG or GIF image.".to_string(),
+            )
+            .into())
+        }
+    };
+    let avatar = format!("data:{};base64,{}", mime, base64::encode(&bytes));
+
+    let mut user = ctx.cache.current_user().await;
+    user.edit(&ctx.http, |profile| profile.avatar(Some(&avatar)))
+        .await?;
+
+    msg.channel_id.say(&ctx.http, "Updated my avatar.").await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[min_args(1)]
+#[usage = "<name>"]
+#[example = "Rustic"]
+#[description = "Change the username of the bot."]
+async fn setname(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let name = args.message().trim();
+
+    if !(2..=32).contains(&name.chars().count()) {
+        return Err(
+            RusticError::BadArgument("The name has to be 2 to 32 characters.".to_string()).into(),
+        );
+    }
+
+    let mut user = ctx.cache.current_user().await;
+    user.edit(&ctx.http, |profile| profile.username(name))
+        .await?;
+
+    msg.channel_id
+        .say(&ctx.http, format!("Changed my name to **{}**.", name))
+        .await?;
+
+    Ok(())
+}
+
+/// Render a single value of a row, whatever its type is.
+fn cell(row: &PgRow, index: usize) -> String {
+    match row.try_get_raw(index) {
+        Ok(value) if value.is_null() => return "NULL".to_string(),
+        Err(why) => return format!("<{}>", why),
+        _ => {}
+    }
+
+    if let Ok(value) = row.try_get::<String, _>(index) {
+        value
+    } else if let Ok(value) = row.try_get::<i64, _>(index) {
+        value.to_string()
+    } else if let Ok(value) = row.try_get::<i32, _>(index) {
+        value.to_string()
+    } else if let Ok(value) = row.try_get::<i16, _>(index) {
+        value.to_string()
+    } else if let Ok(value) = row.try_get::<bool, _>(index) {
+        value.to_string()
+    } else if let Ok(value) = row.try_get::<f64, _>(index) {
+        value.to_string()
+    } else if let Ok(value) = row.try_get::<Vec<String>, _>(index) {
+        format!("{:?}", value)
+    } else if let Ok(value) = row.try_get::<Vec<i64>, _>(index) {
+        format!("{:?}", value)
+    } else {
+        format!("<{}>", row.column(index).type_info().name())
+    }
+}
+
+/// Lay out rows as a plain text table.
+fn table(rows: &[PgRow]) -> String {
+    let columns = match rows.first() {
+        Some(row) => row
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect::<Vec<String>>(),
+        None => return "No rows returned.".to_string(),
+    };
+
+    let cells = rows
+        .iter()
+        .take(MAX_SQL_ROWS)
+        .map(|row| (0..columns.len()).map(|index| cell(row, index)).collect())
+        .collect::<Vec<Vec<String>>>();
+
+    let widths = (0..columns.len())
+        .map(|index| {
+            cells
+                .iter()
+                .map(|row| row[index].chars().count())
+                .chain(std::iter::once(columns[index].chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+
+    let line = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", value, width = width))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    };
+
+    let mut table = line(&columns);
+    table.push('\n');
+    table.push_str(
+        &widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<String>>()
+            .join("-+-"),
+    );
+    for row in &cells {
+        table.push('\n');
+        table.push_str(&line(row));
+    }
+
+    if rows.len() > MAX_SQL_ROWS {
+        table.push_str(&format!("\n... {} more row(s)", rows.len() - MAX_SQL_ROWS));
+    }
+
+    table
+}
+
+#[command]
+#[owners_only]
+#[min_args(1)]
+#[usage = "<query>"]
+#[example = "SELECT guild_id, prefixes FROM guilds LIMIT 5"]
+#[description = "Run a read-only query against the database."]
+async fn sql(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let statement = args
+        .message()
+        .trim()
+        .trim_start_matches("```sql")
+        .trim_matches('`')
+        .trim();
+
+    let pool = ctx
+        .data
+        .read()
+        .await
+        .get::<PgPoolContainer>()
+        .unwrap()
+        .clone();
+
+    // The transaction is never committed, read only mode rejects anything that writes
+    let mut transaction = pool.begin().await?;
+    sqlx::query("SET TRANSACTION READ ONLY")
+        .execute(&mut transaction)
+        .await?;
+    sqlx::query(&format!("SET LOCAL statement_timeout = {}", SQL_TIMEOUT))
+        .execute(&mut transaction)
+        .await?;
+
+    let result = sqlx::query(statement).fetch_all(&mut transaction).await;
+    transaction.rollback().await?;
+
+    let content = match result {
+        Ok(rows) => {
+            let mut table = table(&rows);
+            if table.chars().count() > 1900 {
+                table = table.chars().take(1900).collect();
+                table.push_str("\n...");
+            }
+            format!("```\n{}\n```", table)
+        }
+        Err(why) => format!(":x: | **Query Failed:** ```\n{}\n```", why),
+    };
+
+    msg.channel_id.say(&ctx.http, content).await?;
+
+    Ok(())
+}
+
+#[command("reload-config")]
+#[owners_only]
+#[description = "Re-read the config file and apply the settings which don't need a restart."]
+async fn reload_config(ctx: &Context, msg: &Message) -> CommandResult {
+    let content = match reload(&ctx.data).await {
+        Ok(report) => {
+            info!("Reloaded settings: {}", report);
+            format!("Reloaded the settings: {}.", report)
+        }
+        Err(why) => format!(
+            ":x: | **Reload Failed:** {}\nThe previous settings are still in use.",
+            why
+        ),
+    };
+
+    msg.channel_id.say(&ctx.http, content).await?;
+
+    Ok(())
+}
+
+#[command]
+#[owners_only]
+#[sub_commands(presence_set, presence_clear)]
+#[description = "Show the temporary activity replacing the configured ones."]
+async fn presence(ctx: &Context, msg: &Message) -> CommandResult {
+    let presence = ctx
+        .data
+        .read()
+        .await
+        .get::<PresenceContainer>()
+        .unwrap()
+        .clone();
+
+    let content = match presence.current_override() {
+        Some(temporary) => format!(
+            "Showing `{:?} {}` {}",
+            temporary.activity.kind,
+            temporary.activity.text,
+            match temporary.until {
+                Some(until) => format!(
+                    "for another {} minute(s).",
+                    until.saturating_duration_since(Instant::now()).as_secs() / 60 + 1
+                ),
+                None => "until it's cleared.".to_string(),
+            }
+        ),
+        None => "Rotating the configured activities.".to_string(),
+    };
+
+    msg.channel_id.say(&ctx.http, content).await?;
+
+    Ok(())
+}
+
+#[command("set")]
+#[owners_only]
+#[min_args(3)]
+#[usage = "<playing|listening|watching|competing> <minutes> <text>"]
+#[example = "watching 60 the Halloween event in {guilds} servers"]
+#[description = "Show an activity on every shard instead of the configured ones, 0 minutes keeps it until it's cleared. Supports the `{guilds}`, `{users}`, `{shard}` and `{prefix}` placeholders."]
+async fn presence_set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = args.single::<ActivityKind>().map_err(|why| match why {
+        ArgError::Parse(reason) => RusticError::BadArgument(reason),
+        _ => RusticError::BadArgument("Give me an activity type.".to_string()),
+    })?;
+    let minutes = args.single::<u64>().map_err(|_| {
+        RusticError::BadArgument("Give me how many minutes to show the activity for.".to_string())
+    })?;
+    let text = args.rest().trim().to_string();
+
+    if text.chars().count() > MAX_ACTIVITY_LENGTH {
+        return Err(RusticError::BadArgument(format!(
+            "The activity can't be longer than {} characters.",
+            MAX_ACTIVITY_LENGTH
+        ))
+        .into());
+    }
+
+    let presence = ctx
+        .data
+        .read()
+        .await
+        .get::<PresenceContainer>()
+        .unwrap()
+        .clone();
+    let duration = (minutes > 0).then(|| Duration::from_secs(minutes * 60));
+
+    presence.set_override(ActivitySettings { kind, text }, duration);
+
+    msg.channel_id
+        .say(&ctx.http, "Updated the activity on every shard.")
+        .await?;
+
+    Ok(())
+}
+
+#[command("clear")]
+#[owners_only]
+#[description = "Go back to rotating the configured activities."]
+async fn presence_clear(ctx: &Context, msg: &Message) -> CommandResult {
+    let presence = ctx
+        .data
+        .read()
+        .await
+        .get::<PresenceContainer>()
+        .unwrap()
+        .clone();
+
+    presence.clear_override();
+
+    msg.channel_id
+        .say(&ctx.http, "Cleared the activity override.")
+        .await?;
+
+    Ok(())
+}
+
+/// Read the optional duration and reason following the target of a block.
+fn block_details(args: &mut Args) -> (Option<DateTime<Utc>>, Option<String>) {
+    let expires_at = args
+        .current()
+        .and_then(parse_duration)
+        .map(|duration| Utc::now() + duration);
+    if expires_at.is_some() {
+        args.advance();
+    }
+
+    let reason = Some(args.rest().trim().to_string()).filter(|reason| !reason.is_empty());
+
+    (expires_at, reason)
+}
+
+/// Block or unblock a target and apply the change to the framework right away.
+async fn update_blocklist(
+    ctx: &Context,
+    target: Target,
+    block: Option<(Option<DateTime<Utc>>, Option<String>)>,
+) -> Result<bool, RusticError> {
+    let (pool, framework) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<FrameworkContainer>().unwrap().clone(),
+        )
+    };
+
+    let changed = match block {
+        Some((expires_at, reason)) => {
+            blocklist::block(&pool, target, reason.as_deref(), expires_at).await?;
+            true
+        }
+        None => blocklist::unblock(&pool, target).await?,
+    };
+
+    framework.sync(&pool).await?;
+
+    Ok(changed)
+}
+
+fn describe_block(expires_at: Option<DateTime<Utc>>, reason: &Option<String>) -> String {
+    format!(
+        "{}{}",
+        match expires_at {
+            Some(expires_at) => format!("until <t:{}:f>", expires_at.timestamp()),
+            None => "permanently".to_string(),
+        },
+        match reason {
+            Some(reason) => format!(" for: {}", reason),
+            None => String::new(),
+        }
+    )
+}
+
+fn list_entries(entries: &[Entry], mention: fn(u64) -> String) -> String {
+    if entries.is_empty() {
+        return "Nothing blocked.".to_string();
+    }
+
+    let mut listing = String::new();
+    for entry in entries {
+        let line = format!(
+            "{} since <t:{}:d> {}\n",
+            mention(entry.id),
+            entry.blocked_at.timestamp(),
+            describe_block(entry.expires_at, &entry.reason)
+        );
+
+        // Embed fields are limited to 1024 characters
+        if listing.len() + line.len() > 1000 {
+            listing.push_str("...");
+            break;
+        }
+        listing.push_str(&line);
+    }
+
+    listing
+}
+
+#[command]
+#[owners_only]
+#[sub_commands(blocklist_user, blocklist_guild, blocklist_remove)]
+#[description = "List the blocked users and guilds."]
+async fn blocklist(ctx: &Context, msg: &Message) -> CommandResult {
+    let pool = ctx
+        .data
+        .read()
+        .await
+        .get::<PgPoolContainer>()
+        .unwrap()
+        .clone();
+
+    let users = blocklist::list_users(&pool).await?;
+    let guilds = blocklist::list_guilds(&pool).await?;
+
+    let mut embed = Theme::default().embed(Style::Primary);
+    embed.title("Blocklist");
+    embed.field(
+        format!("Users ({})", users.len()),
+        list_entries(&users, |id| format!("<@{}>", id)),
+        false,
+    );
+    embed.field(
+        format!("Guilds ({})", guilds.len()),
+        list_entries(&guilds, |id| format!("`{}`", id)),
+        false,
+    );
+
+    msg.channel_id
+        .send_message(ctx, |message| message.set_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+#[command("user")]
+#[owners_only]
+#[min_args(1)]
+#[usage = "<user> [duration] [reason]"]
+#[example = "@Spammer 7d Spamming image commands"]
+#[description = "Stop a user from using the bot, for the given duration or permanently."]
+async fn blocklist_user(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let user_id = args
+        .single::<UserId>()
+        .map_err(|_| RusticError::BadArgument("Give me a user mention or ID.".to_string()))?;
+    let (expires_at, reason) = block_details(&mut args);
+
+    update_blocklist(
+        ctx,
+        Target::User(user_id),
+        Some((expires_at, reason.clone())),
+    )
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Blocked <@{}> {}.",
+                user_id.0,
+                describe_block(expires_at, &reason)
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[command("guild")]
+#[owners_only]
+#[min_args(1)]
+#[usage = "<guild id> [duration] [reason]"]
+#[example = "881500906394578974 30d Abusing image commands"]
+#[description = "Stop a guild from using the bot and leave it, for the given duration or permanently."]
+async fn blocklist_guild(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = args
+        .single::<u64>()
+        .map(GuildId)
+        .map_err(|_| RusticError::BadArgument("Give me the ID of a guild.".to_string()))?;
+    let (expires_at, reason) = block_details(&mut args);
+
+    update_blocklist(
+        ctx,
+        Target::Guild(guild_id),
+        Some((expires_at, reason.clone())),
+    )
+    .await?;
+
+    if ctx.cache.guilds().await.contains(&guild_id) {
+        guild_id.leave(&ctx.http).await?;
+    }
+    info!(
+        "Blocked guild {}, requested by {}",
+        guild_id.0,
+        msg.author.tag()
+    );
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Blocked the guild `{}` {}.",
+                guild_id.0,
+                describe_block(expires_at, &reason)
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[command("remove")]
+#[owners_only]
+#[num_args(2)]
+#[usage = "<user|guild> <id>"]
+#[example = "user 881500906394578974"]
+#[description = "Lift the block of a user or a guild."]
+async fn blocklist_remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = args.single::<String>()?;
+    let target =
+        match kind.to_lowercase().as_str() {
+            "user" => Target::User(args.single::<UserId>().map_err(|_| {
+                RusticError::BadArgument("Give me a user mention or ID.".to_string())
+            })?),
+            "guild" => Target::Guild(GuildId(args.single::<u64>().map_err(|_| {
+                RusticError::BadArgument("Give me the ID of a guild.".to_string())
+            })?)),
+            _ => {
+                return Err(RusticError::BadArgument(
+                    "You can only unblock a `user` or a `guild`.".to_string(),
+                )
+                .into())
+            }
+        };
+
+    let content = if update_blocklist(ctx, target, None).await? {
+        "Lifted the block."
+    } else {
+        "That isn't blocked."
+    };
+
+    msg.channel_id.say(&ctx.http, content).await?;
+
+    Ok(())
+}
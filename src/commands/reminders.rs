@@ -0,0 +1,381 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::Response;
+use crate::{
+    error::RusticError,
+    i18n::{language, t, Lang},
+    reminders::{self, Reminder},
+    theme::{theme, Style, Theme},
+    timezones::timezone,
+    utils::{format_duration, parse_duration, parse_when},
+    PgPoolContainer, SchedulerContainer,
+};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::{Message, UserId},
+    prelude::Context,
+};
+
+const MAX_REMINDERS: i64 = 25;
+const MAX_REMINDER_LENGTH: usize = 1000;
+/// Recurring reminders can't repeat more often than this, in minutes.
+const MIN_INTERVAL: i64 = 10;
+/// How far ahead a reminder can be set, in days.
+const MAX_AHEAD: i64 = 5 * 365;
+/// How much of each reminder the list shows.
+const PREVIEW_LENGTH: usize = 80;
+
+/// Read an optional `every <interval>` and the time of a reminder from the start of `words`,
+/// with times of day in `tz`, returning the time, the interval and how many words were used.
+pub fn parse_schedule(
+    lang: Lang,
+    words: &[&str],
+    now: DateTime<Utc>,
+    tz: Tz,
+) -> Result<(DateTime<Utc>, Option<Duration>, usize), RusticError> {
+    let mut used = 0;
+    let mut interval = None;
+
+    if words
+        .first()
+        .is_some_and(|word| word.eq_ignore_ascii_case("every"))
+    {
+        let word = words
+            .get(1)
+            .map_or(String::new(), |word| word.to_lowercase());
+        interval = Some(match word.as_str() {
+            "hour" | "hourly" => Duration::hours(1),
+            "day" | "daily" => Duration::days(1),
+            "week" | "weekly" => Duration::weeks(1),
+            word => parse_duration(word).ok_or_else(|| {
+                RusticError::BadArgument(t!(lang, "reminder-invalid-interval", interval = word))
+            })?,
+        });
+        used = 2;
+    }
+
+    match (parse_when(&words[used..], now, &tz), interval) {
+        (Some((due_at, length)), _) => Ok((due_at, interval, used + length)),
+        // A recurring reminder without a time starts one interval from now
+        (None, Some(interval)) => Ok((now + interval, Some(interval), used)),
+        (None, None) => Err(RusticError::BadArgument(t!(lang, "reminder-invalid-time"))),
+    }
+}
+
+/// The text after the first `count` words.
+fn skip_words(text: &str, count: usize) -> &str {
+    let mut rest = text.trim_start();
+
+    for _ in 0..count {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+
+    rest
+}
+
+/// Store a new reminder, its id is ignored.
+pub async fn remind_response(
+    ctx: &Context,
+    lang: Lang,
+    mut reminder: Reminder,
+) -> Result<Response, RusticError> {
+    reminder.content = reminder.content.trim().to_string();
+    let Reminder {
+        user_id,
+        due_at,
+        interval,
+        ref content,
+        ..
+    } = reminder;
+    let now = Utc::now();
+
+    if content.is_empty() {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "reminder-missing-content"
+        )));
+    }
+
+    if content.chars().count() > MAX_REMINDER_LENGTH {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "reminder-too-long",
+            max = MAX_REMINDER_LENGTH
+        )));
+    }
+
+    if due_at <= now || due_at > now + Duration::days(MAX_AHEAD) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "reminder-out-of-range",
+            years = MAX_AHEAD / 365
+        )));
+    }
+
+    if interval.is_some_and(|interval| interval < Duration::minutes(MIN_INTERVAL)) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "reminder-interval-too-short",
+            minutes = MIN_INTERVAL
+        )));
+    }
+
+    let (pool, scheduler) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<SchedulerContainer>().unwrap().clone(),
+        )
+    };
+
+    if reminders::count(&pool, user_id).await? >= MAX_REMINDERS {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "reminder-limit",
+            max = MAX_REMINDERS
+        )));
+    }
+
+    let id = reminders::create(&pool, &reminder).await?;
+    scheduler.refresh();
+
+    let time = format!("<t:{0}:f> (<t:{0}:R>)", due_at.timestamp());
+
+    Ok(Response::Content(match interval {
+        Some(interval) => t!(
+            lang,
+            "reminder-created-recurring",
+            id = id,
+            time = time,
+            interval = format_duration(interval)
+        ),
+        None => t!(lang, "reminder-created", id = id, time = time),
+    }))
+}
+
+pub async fn remind_list_response(
+    ctx: &Context,
+    lang: Lang,
+    theme: &Theme,
+    user_id: UserId,
+) -> Result<Response, RusticError> {
+    let pool = {
+        let data = ctx.data.read().await;
+        data.get::<PgPoolContainer>().unwrap().clone()
+    };
+
+    let reminders = reminders::list(&pool, user_id).await?;
+
+    if reminders.is_empty() {
+        return Ok(Response::Content(t!(lang, "reminder-none")));
+    }
+
+    let listing = reminders
+        .iter()
+        .map(|reminder| {
+            let mut content = reminder
+                .content
+                .chars()
+                .take(PREVIEW_LENGTH)
+                .collect::<String>()
+                .replace('\n', " ");
+            if reminder.content.chars().count() > PREVIEW_LENGTH {
+                content.push('…');
+            }
+
+            let place = match reminder.channel_id {
+                Some(channel_id) => format!("<#{}>", channel_id.0),
+                None => t!(lang, "reminder-place-dm"),
+            };
+            let mut entry = t!(
+                lang,
+                "reminder-list-entry",
+                id = reminder.id,
+                time = format!("<t:{}:R>", reminder.due_at.timestamp()),
+                place = place,
+                content = content
+            );
+            if let Some(interval) = reminder.interval {
+                entry.push(' ');
+                entry.push_str(&t!(
+                    lang,
+                    "reminder-every",
+                    interval = format_duration(interval)
+                ));
+            }
+
+            entry
+        })
+        .collect::<Vec<String>>();
+
+    let mut embed = theme.embed(Style::Primary);
+    embed.title(t!(lang, "reminder-list", count = reminders.len()));
+    embed.description(listing.join("\n"));
+
+    Ok(Response::Embed(embed))
+}
+
+pub async fn remind_delete_response(
+    ctx: &Context,
+    lang: Lang,
+    user_id: UserId,
+    id: &str,
+) -> Result<Response, RusticError> {
+    let id = id.trim().trim_start_matches('#');
+    let not_found = || RusticError::BadArgument(t!(lang, "reminder-not-found", id = id));
+    let id = id.parse::<i64>().map_err(|_| not_found())?;
+
+    let pool = {
+        let data = ctx.data.read().await;
+        data.get::<PgPoolContainer>().unwrap().clone()
+    };
+
+    if !reminders::delete(&pool, user_id, id).await? {
+        return Err(not_found());
+    }
+
+    Ok(Response::Content(t!(lang, "reminder-deleted", id = id)))
+}
+
+#[command]
+#[aliases("remindme", "reminder")]
+#[bucket = "basic"]
+#[min_args(2)]
+#[usage = "[dm] [every <interval>] <when> <text>"]
+#[example = "in 2h30m Take the pizza out"]
+#[example = "tomorrow 9am Standup meeting"]
+#[example = "dm every day 18:00 Water the plants"]
+#[sub_commands(remind_list, remind_delete)]
+#[description = "Remind you of something later, here or in your DMs with `dm`. The time can be a duration like `2h30m`, a time like `9am` or `17:30`, `tomorrow`, or a date like `2021-12-24 18:00`, all in your timezone (see `timezone`). Start with `every` and an interval like `day` or `12h` to repeat the reminder."]
+async fn remind(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let text = args.rest();
+    let mut words = text.split_whitespace().collect::<Vec<&str>>();
+
+    let skipped = usize::from(
+        words
+            .first()
+            .is_some_and(|word| word.eq_ignore_ascii_case("dm")),
+    );
+    let dm = skipped == 1 || msg.guild_id.is_none();
+    words.drain(..skipped);
+
+    let tz = timezone(ctx, msg.author.id).await.unwrap_or(Tz::UTC);
+    let (due_at, interval, used) = parse_schedule(lang, &words, Utc::now(), tz)?;
+
+    let reminder = Reminder {
+        id: 0,
+        user_id: msg.author.id,
+        guild_id: msg.guild_id,
+        channel_id: (!dm).then_some(msg.channel_id),
+        content: skip_words(text, skipped + used).to_string(),
+        due_at,
+        interval,
+    };
+
+    remind_response(ctx, lang, reminder)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("list")]
+#[description = "List your pending reminders."]
+async fn remind_list(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let theme = theme(ctx, msg.guild_id).await;
+
+    remind_list_response(ctx, lang, &theme, msg.author.id)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("delete")]
+#[aliases("remove", "cancel")]
+#[num_args(1)]
+#[usage = "<id>"]
+#[example = "12"]
+#[description = "Delete one of your reminders, recurring reminders stop repeating."]
+async fn remind_delete(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    remind_delete_response(ctx, lang, msg.author.id, args.message())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(text: &str) -> Result<(DateTime<Utc>, Option<Duration>, usize), RusticError> {
+        let now = DateTime::parse_from_rfc3339("2021-12-17T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+
+        parse_schedule(Lang::parse("en").unwrap(), &words, now, Tz::UTC)
+    }
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_one_off_reminders() {
+        let (due_at, interval, used) = schedule("in 30m check the oven").unwrap();
+        assert_eq!(due_at, at("2021-12-17T10:30:00Z"));
+        assert_eq!(interval, None);
+        assert_eq!(used, 2);
+    }
+
+    #[test]
+    fn parses_recurring_reminders() {
+        let (due_at, interval, used) = schedule("every day 18:00 water the plants").unwrap();
+        assert_eq!(due_at, at("2021-12-17T18:00:00Z"));
+        assert_eq!(interval, Some(Duration::days(1)));
+        assert_eq!(used, 3);
+
+        // Without a time the first one is one interval away
+        let (due_at, interval, used) = schedule("every 12h stretch").unwrap();
+        assert_eq!(due_at, at("2021-12-17T22:00:00Z"));
+        assert_eq!(interval, Some(Duration::hours(12)));
+        assert_eq!(used, 2);
+
+        let (_, interval, _) = schedule("EVERY weekly in 1d").unwrap();
+        assert_eq!(interval, Some(Duration::weeks(1)));
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        for text in ["soon please", "every fortnight", "every", ""] {
+            assert!(schedule(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn skips_words() {
+        assert_eq!(skip_words("  in 2h  take   a break", 2), "take   a break");
+        assert_eq!(skip_words("in 2h", 3), "");
+    }
+}
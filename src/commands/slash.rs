@@ -0,0 +1,1117 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::{config::*, fun::*, image::*, misc::*, reminders::*, tags::*, time::*, Response};
+use crate::{
+    category_of, check_invocation,
+    checks::is_guild_admin,
+    command_bucket,
+    error::{report, ErrorContext, RusticError},
+    i18n::{self, language, t},
+    pipeline::{find_filter, FILTERS},
+    ratelimit::Invocation,
+    reminders::Reminder,
+    rules::RuleTarget,
+    theme::theme,
+    timezones::timezone,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+use serenity::{
+    builder::CreateApplicationCommandOption,
+    framework::standard::CommandResult,
+    model::{
+        interactions::{
+            application_command::{
+                ApplicationCommand, ApplicationCommandInteraction,
+                ApplicationCommandInteractionDataOption,
+                ApplicationCommandInteractionDataOptionValue, ApplicationCommandOptionType,
+            },
+            InteractionResponseType,
+        },
+        prelude::{ChannelId, RoleId, User},
+    },
+    prelude::Context,
+};
+
+/// Register every command as a global application command.
+pub async fn register(ctx: &Context) -> CommandResult {
+    ApplicationCommand::set_global_application_commands(&ctx.http, |commands| {
+        for filter in FILTERS {
+            commands.create_application_command(|command| {
+                command
+                    .name(filter.name())
+                    .description(filter.description())
+                    .create_option(|option| {
+                        option
+                            .name("member")
+                            .description("The member whose avatar to use")
+                            .kind(ApplicationCommandOptionType::User)
+                            .required(false)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("image")
+                            .description("A link to an image or a custom emoji")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+            });
+        }
+
+        commands
+            .create_application_command(|command| {
+                command
+                    .name("ping")
+                    .description("Check if the bot is working.")
+            })
+            .create_application_command(|command| {
+                command
+                    .name("about")
+                    .description("Tells you information about the bot itself.")
+            })
+            .create_application_command(|command| {
+                command
+                    .name("cat")
+                    .description("Find some cute cat pictures!")
+            })
+            .create_application_command(|command| {
+                command
+                    .name("eightball")
+                    .description("Ask a question to the magic 8ball")
+                    .create_option(|option| {
+                        option
+                            .name("question")
+                            .description("The question to ask")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("urban")
+                    .description("Searches urban dictionary.")
+                    .create_option(|option| {
+                        option
+                            .name("word")
+                            .description("The word to look up")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("prefix")
+                    .description("Manage the prefixes of this guild.")
+                    .create_option(|option| {
+                        option
+                            .name("list")
+                            .description("List all the prefixes of this guild.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("set")
+                            .description("Replace all the prefixes with a single prefix.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("prefix")
+                                    .description("The new prefix")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("add")
+                            .description("Add another prefix the bot will respond to.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("prefix")
+                                    .description("The prefix to add")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("remove")
+                            .description("Remove one of the prefixes.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("prefix")
+                                    .description("The prefix to remove")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("reset")
+                            .description("Reset the prefixes back to the default.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("adminrole")
+                    .description("Show or change the role allowed to configure the bot.")
+                    .create_option(|option| {
+                        option
+                            .name("role")
+                            .description("The new bot admin role")
+                            .kind(ApplicationCommandOptionType::Role)
+                            .required(false)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("none")
+                            .description("Remove the bot admin role")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("language")
+                    .description("Show or change the language the bot answers in on this guild.")
+                    .create_option(language_option)
+                    .create_option(|option| {
+                        option
+                            .name("reset")
+                            .description("Go back to English")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("mylanguage")
+                    .description("Show or change the language the bot always answers you in.")
+                    .create_option(language_option)
+                    .create_option(|option| {
+                        option
+                            .name("reset")
+                            .description("Go back to the language of each guild")
+                            .kind(ApplicationCommandOptionType::Boolean)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("timezone")
+                    .description("Show or change your timezone.")
+                    .create_option(|option| {
+                        option
+                            .name("show")
+                            .description("Show your timezone or the timezone of another member.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("member")
+                                    .description("The member to show the timezone of")
+                                    .kind(ApplicationCommandOptionType::User)
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("set")
+                            .description("Pick your timezone.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("timezone")
+                                    .description("A big city near you or a name like Europe/London")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("reset")
+                            .description("Remove your timezone, times are read in UTC again.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("time")
+                    .description("Show the current time somewhere or convert a time between timezones.")
+                    .create_option(|option| {
+                        option
+                            .name("member")
+                            .description("Show the current time of this member")
+                            .kind(ApplicationCommandOptionType::User)
+                            .required(false)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("time")
+                            .description("A time like 9pm, 17:30 or tomorrow 9am, leave it out for now")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("from")
+                            .description("The timezone the time is in, yours if left out")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("to")
+                            .description("The timezone to convert the time to")
+                            .kind(ApplicationCommandOptionType::String)
+                            .required(false)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("theme")
+                    .description("Preview or change the look of the bot's embeds in this guild.")
+                    .create_option(|option| {
+                        option
+                            .name("show")
+                            .description("Preview the current theme.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("colour")
+                            .description("Change one of the embed colours.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("style")
+                                    .description("The colour to change")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .add_string_choice("Primary", "primary")
+                                    .add_string_choice("Error", "error")
+                                    .add_string_choice("Success", "success")
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("colour")
+                                    .description("A hex code like #F05B4A, a name like blurple, or reset")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("footer")
+                            .description("Add a footer to the bot's embeds.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("text")
+                                    .description("The footer, leave it out to remove it")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("author")
+                            .description("Show a name at the top of the bot's embeds.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("name")
+                                    .description("The name, leave it out to remove it")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("icon")
+                            .description("Show an icon next to the author name.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("url")
+                                    .description("An https link to the icon, leave it out to remove it")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("reset")
+                            .description("Reset the theme back to the default.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("remind")
+                    .description("Set, list and delete your reminders.")
+                    .create_option(|option| {
+                        option
+                            .name("create")
+                            .description("Remind you of something later.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("when")
+                                    .description("A duration like 2h30m, a time like 9am or 17:30, tomorrow or a date, in your timezone")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("text")
+                                    .description("What to remind you of")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("every")
+                                    .description("Repeat the reminder, like day, week or 12h")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(false)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("dm")
+                                    .description("Send the reminder in your DMs")
+                                    .kind(ApplicationCommandOptionType::Boolean)
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("list")
+                            .description("List your pending reminders.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("delete")
+                            .description("Delete one of your reminders.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("id")
+                                    .description("The id shown in the reminder list")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("tag")
+                    .description("Show and manage the tags of this guild.")
+                    .create_option(|option| {
+                        option
+                            .name("show")
+                            .description("Show a tag.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("name")
+                                    .description("The name of the tag")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("args")
+                                    .description("Text used in place of {args}")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(false)
+                            })
+                    });
+
+                for (name, description) in [
+                    ("create", "Create a tag owned by you."),
+                    ("edit", "Replace the content of a tag you own."),
+                ] {
+                    command.create_option(|option| {
+                        option
+                            .name(name)
+                            .description(description)
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("name")
+                                    .description("The name of the tag")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("content")
+                                    .description("What the tag shows, {user}, {channel} and {args} are filled in")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    });
+                }
+
+                for (name, description) in [
+                    ("delete", "Delete a tag you own."),
+                    ("info", "Show who owns a tag and how often it was used."),
+                    ("raw", "Show the content of a tag without filling in the variables."),
+                ] {
+                    command.create_option(|option| {
+                        option
+                            .name(name)
+                            .description(description)
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("name")
+                                    .description("The name of the tag")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    });
+                }
+
+                command
+                    .create_option(|option| {
+                        option
+                            .name("list")
+                            .description("List the tags of this guild, or the tags a member owns.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("member")
+                                    .description("Only list the tags of this member")
+                                    .kind(ApplicationCommandOptionType::User)
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("search")
+                            .description("Find tags with the text in their name.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("text")
+                                    .description("The text to look for")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("transfer")
+                            .description("Give a tag you own to another member.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("name")
+                                    .description("The name of the tag")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("member")
+                                    .description("The new owner")
+                                    .kind(ApplicationCommandOptionType::User)
+                                    .required(true)
+                            })
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("alias")
+                    .description("Manage the command aliases of this guild.")
+                    .create_option(|option| {
+                        option
+                            .name("list")
+                            .description("List the command aliases of this guild.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("add")
+                            .description("Add another name a command can be used with.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("alias")
+                                    .description("The new name")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("command")
+                                    .description("The command it stands for")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("remove")
+                            .description("Remove one of the aliases.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("alias")
+                                    .description("The alias to remove")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                    })
+            })
+            .create_application_command(|command| {
+                command
+                    .name("commands")
+                    .description("Enable or disable commands in this guild.")
+                    .create_option(|option| {
+                        option
+                            .name("list")
+                            .description(
+                                "Show where commands and groups have been enabled or disabled.",
+                            )
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    });
+
+                for (name, description) in [
+                    (
+                        "disable",
+                        "Disable a command or group in this server, a channel or for a role.",
+                    ),
+                    (
+                        "enable",
+                        "Enable a command or group in this server, a channel or for a role.",
+                    ),
+                    (
+                        "reset",
+                        "Remove a rule, the command falls back to the broader rules.",
+                    ),
+                ] {
+                    command.create_option(|option| {
+                        option
+                            .name(name)
+                            .description(description)
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("name")
+                                    .description("The command or group")
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .required(true)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("channel")
+                                    .description("Only apply the rule in this channel")
+                                    .kind(ApplicationCommandOptionType::Channel)
+                                    .required(false)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("role")
+                                    .description("Only apply the rule to this role")
+                                    .kind(ApplicationCommandOptionType::Role)
+                                    .required(false)
+                            })
+                    });
+                }
+
+                command.create_option(|option| {
+                    option
+                        .name("silent")
+                        .description(
+                            "Ignore disabled commands silently instead of telling the member.",
+                        )
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("enabled")
+                                .description("Whether to ignore disabled commands silently")
+                                .kind(ApplicationCommandOptionType::Boolean)
+                                .required(true)
+                        })
+                })
+            })
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn language_option(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("language")
+        .description("The new language")
+        .kind(ApplicationCommandOptionType::String)
+        .required(false);
+
+    for (code, name, _) in i18n::LANGUAGES {
+        option.add_string_choice(name, code);
+    }
+
+    option
+}
+
+fn string_option(
+    options: &[ApplicationCommandInteractionDataOption],
+    name: &str,
+) -> Option<String> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(ApplicationCommandInteractionDataOptionValue::String(value)) => {
+                Some(value.clone())
+            }
+            _ => None,
+        })
+}
+
+fn user_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> Option<User> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(ApplicationCommandInteractionDataOptionValue::User(user, _)) => Some(user.clone()),
+            _ => None,
+        })
+}
+
+fn role_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> Option<RoleId> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(ApplicationCommandInteractionDataOptionValue::Role(role)) => Some(role.id),
+            _ => None,
+        })
+}
+
+fn channel_option(
+    options: &[ApplicationCommandInteractionDataOption],
+    name: &str,
+) -> Option<ChannelId> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(ApplicationCommandInteractionDataOptionValue::Channel(channel)) => {
+                Some(channel.id)
+            }
+            _ => None,
+        })
+}
+
+fn bool_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> Option<bool> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(ApplicationCommandInteractionDataOptionValue::Boolean(value)) => Some(*value),
+            _ => None,
+        })
+}
+
+/// Run the slash command through the same implementation as its prefix counterpart.
+pub async fn dispatch(ctx: &Context, interaction: &ApplicationCommandInteraction) -> CommandResult {
+    // Image and web commands can take longer than the 3 seconds Discord waits for a response
+    interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+        })
+        .await?;
+
+    let response = match run(ctx, interaction).await {
+        Ok(response) => response,
+        Err(error) => {
+            let context = ErrorContext {
+                command: interaction.data.name.clone(),
+                guild_id: interaction.guild_id,
+                channel_id: interaction.channel_id,
+                user_id: interaction.user.id,
+                args: interaction
+                    .data
+                    .options
+                    .iter()
+                    .map(|option| match &option.value {
+                        Some(value) => format!("{}: {}", option.name, value),
+                        None => option.name.clone(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            };
+            let error_id = report(ctx, &error, &context).await;
+            let lang = language(ctx, interaction.guild_id, interaction.user.id).await;
+            let theme = theme(ctx, interaction.guild_id).await;
+
+            Response::Embed(error.embed(lang, &theme, &error_id))
+        }
+    };
+
+    response.followup(ctx, interaction).await
+}
+
+async fn run(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+) -> Result<Response, RusticError> {
+    let invocation = Invocation {
+        user_id: interaction.user.id,
+        channel_id: interaction.channel_id,
+        guild_id: interaction.guild_id,
+        category_id: category_of(ctx, interaction.channel_id).await,
+        roles: interaction
+            .member
+            .as_ref()
+            .map(|member| member.roles.clone())
+            .unwrap_or_default(),
+    };
+    let name = interaction.data.name.as_str();
+    let lang = language(ctx, interaction.guild_id, interaction.user.id).await;
+    let theme = theme(ctx, interaction.guild_id).await;
+
+    if let Err(refusal) = check_invocation(ctx, name, command_bucket(name), &invocation).await {
+        return Ok(Response::Content(refusal.message(lang)));
+    }
+
+    let response = match name {
+        "ping" => ping_response(ctx, lang, &theme).await?,
+        "about" => about_response(ctx, lang, &theme).await?,
+        "mylanguage" => {
+            let new_language = if bool_option(&interaction.data.options, "reset").unwrap_or(false) {
+                Some("reset".to_string())
+            } else {
+                string_option(&interaction.data.options, "language")
+            };
+
+            mylanguage_response(ctx, lang, interaction.user.id, new_language.as_deref()).await?
+        }
+        "timezone" => {
+            let user_id = interaction.user.id;
+
+            match interaction.data.options.first() {
+                Some(subcommand) if subcommand.name == "set" => {
+                    let text = string_option(&subcommand.options, "timezone").unwrap_or_default();
+                    timezone_set_response(ctx, lang, user_id, &text).await?
+                }
+                Some(subcommand) if subcommand.name == "reset" => {
+                    timezone_set_response(ctx, lang, user_id, "reset").await?
+                }
+                Some(subcommand) => {
+                    let member =
+                        user_option(&subcommand.options, "member").map_or(user_id, |user| user.id);
+                    timezone_show_response(ctx, lang, user_id, member).await?
+                }
+                None => timezone_show_response(ctx, lang, user_id, user_id).await?,
+            }
+        }
+        "time" => {
+            let options = &interaction.data.options;
+
+            match user_option(options, "member") {
+                Some(member) => {
+                    timezone_show_response(ctx, lang, interaction.user.id, member.id).await?
+                }
+                None => {
+                    let when = string_option(options, "time");
+                    let from = string_option(options, "from");
+                    let to = string_option(options, "to");
+                    time_response(
+                        ctx,
+                        lang,
+                        interaction.user.id,
+                        when.as_deref(),
+                        from.as_deref(),
+                        to.as_deref(),
+                    )
+                    .await?
+                }
+            }
+        }
+        "cat" => cat_response(ctx, lang, &theme).await?,
+        "eightball" => eightball_response(lang),
+        "urban" => {
+            let word = string_option(&interaction.data.options, "word").unwrap_or_default();
+            urban_response(ctx, lang, &theme, &word).await?
+        }
+        "remind" => {
+            let user_id = interaction.user.id;
+            let subcommand = match interaction.data.options.first() {
+                Some(subcommand) => subcommand,
+                None => return remind_list_response(ctx, lang, &theme, user_id).await,
+            };
+            let options = &subcommand.options;
+
+            match subcommand.name.as_str() {
+                "create" => {
+                    let when = string_option(options, "when").unwrap_or_default();
+                    let every = string_option(options, "every");
+                    let dm = bool_option(options, "dm").unwrap_or(false)
+                        || interaction.guild_id.is_none();
+
+                    let schedule = match &every {
+                        Some(every) => format!("every {} {}", every, when),
+                        None => when,
+                    };
+                    let words = schedule.split_whitespace().collect::<Vec<&str>>();
+                    let tz = timezone(ctx, user_id).await.unwrap_or(Tz::UTC);
+                    let (due_at, interval, used) = parse_schedule(lang, &words, Utc::now(), tz)?;
+
+                    // The time has its own option, so all of it has to be understood
+                    if used < words.len() {
+                        return Err(RusticError::BadArgument(t!(lang, "reminder-invalid-time")));
+                    }
+
+                    let reminder = Reminder {
+                        id: 0,
+                        user_id,
+                        guild_id: interaction.guild_id,
+                        channel_id: (!dm).then_some(interaction.channel_id),
+                        content: string_option(options, "text").unwrap_or_default(),
+                        due_at,
+                        interval,
+                    };
+
+                    remind_response(ctx, lang, reminder).await?
+                }
+                "delete" => {
+                    let id = string_option(options, "id").unwrap_or_default();
+                    remind_delete_response(ctx, lang, user_id, &id).await?
+                }
+                _ => remind_list_response(ctx, lang, &theme, user_id).await?,
+            }
+        }
+        "tag" => {
+            let (guild_id, member) = match (interaction.guild_id, &interaction.member) {
+                (Some(guild_id), Some(member)) => (guild_id, member),
+                _ => return Ok(Response::Content(t!(lang, "dispatch-only-for-guilds"))),
+            };
+
+            let subcommand = match interaction.data.options.first() {
+                Some(subcommand) => subcommand,
+                None => return tag_list_response(ctx, lang, guild_id, None).await,
+            };
+            let options = &subcommand.options;
+            let name = string_option(options, "name").unwrap_or_default();
+            let content = string_option(options, "content").unwrap_or_default();
+
+            match subcommand.name.as_str() {
+                "show" => {
+                    let args = string_option(options, "args").unwrap_or_default();
+                    tag_show_response(
+                        ctx,
+                        lang,
+                        guild_id,
+                        interaction.user.id,
+                        interaction.channel_id,
+                        &name,
+                        &args,
+                    )
+                    .await?
+                }
+                "create" => {
+                    tag_create_response(ctx, lang, guild_id, interaction.user.id, &name, &content)
+                        .await?
+                }
+                "edit" => tag_edit_response(ctx, lang, guild_id, member, &name, &content).await?,
+                "delete" => tag_delete_response(ctx, lang, guild_id, member, &name).await?,
+                "info" => tag_info_response(ctx, lang, guild_id, &name).await?,
+                "raw" => tag_raw_response(ctx, lang, guild_id, &name).await?,
+                "search" => {
+                    let text = string_option(options, "text").unwrap_or_default();
+                    tag_search_response(ctx, lang, guild_id, &text).await?
+                }
+                "transfer" => match user_option(options, "member") {
+                    Some(new_owner) => {
+                        tag_transfer_response(ctx, lang, guild_id, member, &name, &new_owner)
+                            .await?
+                    }
+                    None => tag_list_response(ctx, lang, guild_id, None).await?,
+                },
+                _ => {
+                    let owner = user_option(options, "member");
+                    tag_list_response(ctx, lang, guild_id, owner.as_ref()).await?
+                }
+            }
+        }
+        "prefix" | "adminrole" | "commands" | "alias" | "language" | "theme" => {
+            let (guild_id, member) = match (interaction.guild_id, &interaction.member) {
+                (Some(guild_id), Some(member)) => (guild_id, member),
+                _ => return Ok(Response::Content(t!(lang, "dispatch-only-for-guilds"))),
+            };
+
+            if !is_guild_admin(ctx, guild_id, member).await {
+                return Ok(Response::Content(t!(
+                    lang,
+                    "dispatch-check-failed",
+                    reason = t!(lang, "check-guild-admin")
+                )));
+            }
+
+            if interaction.data.name == "theme" {
+                let subcommand = match interaction.data.options.first() {
+                    Some(subcommand) => subcommand,
+                    None => return theme_show_response(ctx, lang, guild_id).await,
+                };
+                let options = &subcommand.options;
+
+                match subcommand.name.as_str() {
+                    "colour" => {
+                        let style = string_option(options, "style").unwrap_or_default();
+                        let colour = string_option(options, "colour").unwrap_or_default();
+                        theme_colour_response(ctx, lang, guild_id, &style, &colour).await?
+                    }
+                    "footer" => {
+                        let footer = string_option(options, "text");
+                        theme_footer_response(ctx, lang, guild_id, footer.as_deref()).await?
+                    }
+                    "author" => {
+                        let name = string_option(options, "name");
+                        theme_author_response(ctx, lang, guild_id, name.as_deref()).await?
+                    }
+                    "icon" => {
+                        let icon = string_option(options, "url");
+                        theme_icon_response(ctx, lang, guild_id, icon.as_deref()).await?
+                    }
+                    "reset" => theme_reset_response(ctx, lang, guild_id).await?,
+                    _ => theme_show_response(ctx, lang, guild_id).await?,
+                }
+            } else if interaction.data.name == "language" {
+                let new_language =
+                    if bool_option(&interaction.data.options, "reset").unwrap_or(false) {
+                        Some("reset".to_string())
+                    } else {
+                        string_option(&interaction.data.options, "language")
+                    };
+
+                language_response(ctx, lang, guild_id, new_language.as_deref()).await?
+            } else if interaction.data.name == "alias" {
+                match interaction.data.options.first() {
+                    Some(subcommand) => {
+                        let alias = string_option(&subcommand.options, "alias").unwrap_or_default();
+
+                        match subcommand.name.as_str() {
+                            "add" => {
+                                let command = string_option(&subcommand.options, "command")
+                                    .unwrap_or_default();
+                                alias_add_response(ctx, lang, guild_id, &alias, &command).await?
+                            }
+                            "remove" => alias_remove_response(ctx, lang, guild_id, &alias).await?,
+                            _ => alias_list_response(ctx, lang, guild_id).await?,
+                        }
+                    }
+                    None => alias_list_response(ctx, lang, guild_id).await?,
+                }
+            } else if interaction.data.name == "commands" {
+                let subcommand = match interaction.data.options.first() {
+                    Some(subcommand) => subcommand,
+                    None => return commands_list_response(ctx, lang, guild_id).await,
+                };
+                let options = &subcommand.options;
+                let enabled = match subcommand.name.as_str() {
+                    "disable" => Some(false),
+                    "enable" => Some(true),
+                    "reset" => None,
+                    "silent" => {
+                        let silent = bool_option(options, "enabled").unwrap_or(false);
+                        return commands_silent_response(ctx, lang, guild_id, silent).await;
+                    }
+                    _ => return commands_list_response(ctx, lang, guild_id).await,
+                };
+
+                let target = match (
+                    channel_option(options, "channel"),
+                    role_option(options, "role"),
+                ) {
+                    (Some(_), Some(_)) => {
+                        return Err(RusticError::BadArgument(t!(lang, "rules-target-conflict")))
+                    }
+                    (Some(channel_id), None) => RuleTarget::Channel(channel_id),
+                    (None, Some(role_id)) => RuleTarget::Role(role_id),
+                    (None, None) => RuleTarget::Guild,
+                };
+                let name = string_option(options, "name").unwrap_or_default();
+
+                commands_set_response(ctx, lang, guild_id, &name, target, enabled).await?
+            } else if interaction.data.name == "adminrole" {
+                let new_role = if bool_option(&interaction.data.options, "none").unwrap_or(false) {
+                    Some(None)
+                } else {
+                    role_option(&interaction.data.options, "role").map(Some)
+                };
+
+                adminrole_response(ctx, lang, guild_id, new_role).await?
+            } else {
+                match interaction.data.options.first() {
+                    Some(subcommand) => {
+                        let prefix =
+                            string_option(&subcommand.options, "prefix").unwrap_or_default();
+
+                        match subcommand.name.as_str() {
+                            "set" => prefix_set_response(ctx, lang, guild_id, prefix).await?,
+                            "add" => prefix_add_response(ctx, lang, guild_id, prefix).await?,
+                            "remove" => prefix_remove_response(ctx, lang, guild_id, prefix).await?,
+                            "reset" => prefix_reset_response(ctx, lang, guild_id).await?,
+                            _ => prefix_list_response(ctx, lang, guild_id).await?,
+                        }
+                    }
+                    None => prefix_list_response(ctx, lang, guild_id).await?,
+                }
+            }
+        }
+        name => match find_filter(name) {
+            Some(filter) => {
+                let options = &interaction.data.options;
+                let url = match (
+                    string_option(options, "image"),
+                    user_option(options, "member"),
+                ) {
+                    (None, Some(member)) => avatar(&member),
+                    (image, _) => {
+                        let argument = image.unwrap_or_default();
+                        resolve_image(
+                            ctx,
+                            ImageQuery {
+                                author: &interaction.user,
+                                guild_id: interaction.guild_id,
+                                channel_id: interaction.channel_id,
+                                message: None,
+                                argument: &argument,
+                            },
+                        )
+                        .await?
+                    }
+                };
+
+                filter_response(ctx, lang, filter, &url).await?
+            }
+            None => Response::Content(t!(lang, "slash-unknown-command")),
+        },
+    };
+
+    Ok(response)
+}
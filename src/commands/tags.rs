@@ -0,0 +1,574 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::Response;
+use crate::{
+    checks::is_guild_admin,
+    error::RusticError,
+    i18n::{language, t, Lang},
+    is_command_name,
+    tags::{self, Tag},
+    theme::{theme, Style},
+    PgPoolContainer,
+};
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::{ChannelId, GuildId, Member, Message, User, UserId},
+    prelude::Context,
+    utils::ArgumentConvert,
+};
+use sqlx::PgPool;
+
+const MAX_TAGS: i64 = 250;
+const MAX_TAG_NAME_LENGTH: usize = 32;
+const MAX_TAG_LENGTH: usize = 1800;
+/// Discord rejects messages longer than this.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Check that a tag name is usable, returning it in lowercase.
+fn validate_name(lang: Lang, name: &str) -> Result<String, RusticError> {
+    let name = name.to_lowercase();
+
+    if name.is_empty()
+        || name.chars().count() > MAX_TAG_NAME_LENGTH
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "tag-name-invalid",
+            max = MAX_TAG_NAME_LENGTH
+        )));
+    }
+
+    let subcommand = TAG_COMMAND
+        .options
+        .sub_commands
+        .iter()
+        .flat_map(|command| command.options.names.iter())
+        .any(|n| n.eq_ignore_ascii_case(&name));
+
+    if subcommand || is_command_name(&name) {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "command-name-taken",
+            name = name.as_str()
+        )));
+    }
+
+    Ok(name)
+}
+
+fn validate_content(lang: Lang, content: &str) -> Result<(), RusticError> {
+    if content.trim().is_empty() {
+        return Err(RusticError::BadArgument(t!(lang, "tag-content-missing")));
+    }
+
+    if content.chars().count() > MAX_TAG_LENGTH {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "tag-too-long",
+            max = MAX_TAG_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+async fn get_pool(ctx: &Context) -> PgPool {
+    let data = ctx.data.read().await;
+    data.get::<PgPoolContainer>().unwrap().clone()
+}
+
+async fn find_tag(
+    lang: Lang,
+    pool: &PgPool,
+    guild_id: GuildId,
+    name: &str,
+) -> Result<Tag, RusticError> {
+    tags::get(pool, guild_id, &name.to_lowercase())
+        .await?
+        .ok_or_else(|| RusticError::BadArgument(t!(lang, "tag-not-found", name = name)))
+}
+
+/// Find a tag the member may change, either because they own it or they are a guild admin.
+async fn owned_tag(
+    ctx: &Context,
+    lang: Lang,
+    pool: &PgPool,
+    guild_id: GuildId,
+    member: &Member,
+    name: &str,
+) -> Result<Tag, RusticError> {
+    let tag = find_tag(lang, pool, guild_id, name).await?;
+
+    if tag.owner_id != member.user.id && !is_guild_admin(ctx, guild_id, member).await {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "tag-not-owned",
+            name = tag.name.as_str()
+        )));
+    }
+
+    Ok(tag)
+}
+
+/// List tag names, stopping before the message gets too long.
+fn name_listing(lang: Lang, names: &[String]) -> String {
+    let mut listing = String::new();
+
+    for (shown, name) in names.iter().enumerate() {
+        let entry = format!("`{}`", name);
+
+        if listing.len() + entry.len() + 64 > MAX_MESSAGE_LENGTH {
+            listing.push(' ');
+            listing.push_str(&t!(lang, "tag-list-more", count = names.len() - shown));
+            break;
+        }
+
+        if !listing.is_empty() {
+            listing.push_str(", ");
+        }
+        listing.push_str(&entry);
+    }
+
+    listing
+}
+
+pub async fn tag_show_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    user_id: UserId,
+    channel_id: ChannelId,
+    name: &str,
+    args: &str,
+) -> Result<Response, RusticError> {
+    let pool = get_pool(ctx).await;
+
+    let content = tags::use_tag(&pool, guild_id, &name.to_lowercase())
+        .await?
+        .ok_or_else(|| RusticError::BadArgument(t!(lang, "tag-not-found", name = name)))?;
+
+    let content = tags::render(&content, user_id, channel_id, args);
+
+    Ok(Response::Content(
+        content.chars().take(MAX_MESSAGE_LENGTH).collect(),
+    ))
+}
+
+pub async fn tag_create_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    owner_id: UserId,
+    name: &str,
+    content: &str,
+) -> Result<Response, RusticError> {
+    let name = validate_name(lang, name)?;
+    validate_content(lang, content)?;
+
+    let pool = get_pool(ctx).await;
+
+    if tags::count(&pool, guild_id).await? >= MAX_TAGS {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "tag-limit",
+            max = MAX_TAGS
+        )));
+    }
+
+    if !tags::create(&pool, guild_id, &name, content, owner_id).await? {
+        return Err(RusticError::BadArgument(t!(
+            lang,
+            "tag-exists",
+            name = name.as_str()
+        )));
+    }
+
+    Ok(Response::Content(t!(lang, "tag-created", name = name)))
+}
+
+pub async fn tag_edit_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    member: &Member,
+    name: &str,
+    content: &str,
+) -> Result<Response, RusticError> {
+    validate_content(lang, content)?;
+
+    let pool = get_pool(ctx).await;
+    let tag = owned_tag(ctx, lang, &pool, guild_id, member, name).await?;
+
+    tags::edit(&pool, guild_id, &tag.name, content).await?;
+
+    Ok(Response::Content(t!(lang, "tag-edited", name = tag.name)))
+}
+
+pub async fn tag_delete_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    member: &Member,
+    name: &str,
+) -> Result<Response, RusticError> {
+    let pool = get_pool(ctx).await;
+    let tag = owned_tag(ctx, lang, &pool, guild_id, member, name).await?;
+
+    tags::delete(&pool, guild_id, &tag.name).await?;
+
+    Ok(Response::Content(t!(lang, "tag-deleted", name = tag.name)))
+}
+
+pub async fn tag_transfer_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    member: &Member,
+    name: &str,
+    new_owner: &User,
+) -> Result<Response, RusticError> {
+    if new_owner.bot {
+        return Err(RusticError::BadArgument(t!(lang, "tag-bot-owner")));
+    }
+
+    let pool = get_pool(ctx).await;
+    let tag = owned_tag(ctx, lang, &pool, guild_id, member, name).await?;
+
+    tags::transfer(&pool, guild_id, &tag.name, new_owner.id).await?;
+
+    Ok(Response::Content(t!(
+        lang,
+        "tag-transferred",
+        name = tag.name,
+        owner = new_owner.name.as_str()
+    )))
+}
+
+pub async fn tag_info_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    name: &str,
+) -> Result<Response, RusticError> {
+    let pool = get_pool(ctx).await;
+    let tag = find_tag(lang, &pool, guild_id, name).await?;
+
+    let theme = theme(ctx, Some(guild_id)).await;
+    let mut embed = theme.embed(Style::Primary);
+    embed.title(t!(lang, "tag-info-title", name = tag.name));
+    embed.field(
+        t!(lang, "tag-info-owner"),
+        format!("<@{}>", tag.owner_id.0),
+        true,
+    );
+    embed.field(t!(lang, "tag-info-uses"), tag.uses, true);
+    embed.field(
+        t!(lang, "tag-info-created"),
+        format!("<t:{}:f>", tag.created_at.timestamp()),
+        true,
+    );
+    if let Some(updated_at) = tag.updated_at {
+        embed.field(
+            t!(lang, "tag-info-edited"),
+            format!("<t:{}:f>", updated_at.timestamp()),
+            true,
+        );
+    }
+
+    Ok(Response::Embed(embed))
+}
+
+pub async fn tag_list_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    owner: Option<&User>,
+) -> Result<Response, RusticError> {
+    let pool = get_pool(ctx).await;
+    let names = tags::list(&pool, guild_id, owner.map(|user| user.id)).await?;
+
+    if names.is_empty() {
+        return Ok(Response::Content(match owner {
+            Some(user) => t!(lang, "tag-list-owner-none", owner = user.name.as_str()),
+            None => t!(lang, "tag-list-none"),
+        }));
+    }
+
+    Ok(Response::Content(match owner {
+        Some(user) => t!(
+            lang,
+            "tag-list-owner",
+            owner = user.name.as_str(),
+            count = names.len(),
+            tags = name_listing(lang, &names)
+        ),
+        None => t!(
+            lang,
+            "tag-list",
+            count = names.len(),
+            tags = name_listing(lang, &names)
+        ),
+    }))
+}
+
+pub async fn tag_search_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    text: &str,
+) -> Result<Response, RusticError> {
+    let pool = get_pool(ctx).await;
+    let names = tags::search(&pool, guild_id, &text.to_lowercase()).await?;
+
+    if names.is_empty() {
+        return Ok(Response::Content(t!(lang, "tag-search-none", text = text)));
+    }
+
+    Ok(Response::Content(t!(
+        lang,
+        "tag-search",
+        text = text,
+        tags = name_listing(lang, &names)
+    )))
+}
+
+pub async fn tag_raw_response(
+    ctx: &Context,
+    lang: Lang,
+    guild_id: GuildId,
+    name: &str,
+) -> Result<Response, RusticError> {
+    let pool = get_pool(ctx).await;
+    let tag = find_tag(lang, &pool, guild_id, name).await?;
+
+    // Keep the content from closing the code block early
+    Ok(Response::Content(format!(
+        "```\n{}\n```",
+        tag.content.replace("```", "`\u{200B}``")
+    )))
+}
+
+fn single_name(lang: Lang, args: &mut Args) -> Result<String, RusticError> {
+    args.single::<String>()
+        .map_err(|_| RusticError::BadArgument(t!(lang, "tag-name-missing")))
+}
+
+#[command]
+#[only_in(guilds)]
+#[usage = "[name] [args]"]
+#[example = "rules"]
+#[bucket = "basic"]
+#[sub_commands(
+    tag_create,
+    tag_edit,
+    tag_delete,
+    tag_info,
+    tag_list,
+    tag_search,
+    tag_raw,
+    tag_transfer
+)]
+#[description = "Show a tag, tags can also be used directly by their name. Not providing a name lists the tags of this guild. In the content `{user}`, `{channel}` and `{args}` are replaced with you, the channel and the text after the tag name."]
+async fn tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let guild_id = msg.guild_id.unwrap();
+
+    let response = if args.is_empty() {
+        tag_list_response(ctx, lang, guild_id, None).await?
+    } else {
+        let name = single_name(lang, &mut args)?;
+        tag_show_response(
+            ctx,
+            lang,
+            guild_id,
+            msg.author.id,
+            msg.channel_id,
+            &name,
+            args.rest(),
+        )
+        .await?
+    };
+
+    response.send(ctx, msg).await
+}
+
+#[command("create")]
+#[only_in(guilds)]
+#[min_args(2)]
+#[usage = "<name> <content>"]
+#[example = "rules Be nice, {user}!"]
+#[description = "Create a tag owned by you."]
+async fn tag_create(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = single_name(lang, &mut args)?;
+
+    tag_create_response(
+        ctx,
+        lang,
+        msg.guild_id.unwrap(),
+        msg.author.id,
+        &name,
+        args.rest(),
+    )
+    .await?
+    .send(ctx, msg)
+    .await
+}
+
+#[command("edit")]
+#[only_in(guilds)]
+#[min_args(2)]
+#[usage = "<name> <content>"]
+#[example = "rules Be very nice, {user}!"]
+#[description = "Replace the content of a tag you own."]
+async fn tag_edit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = single_name(lang, &mut args)?;
+    let member = msg.member(ctx).await?;
+
+    tag_edit_response(
+        ctx,
+        lang,
+        msg.guild_id.unwrap(),
+        &member,
+        &name,
+        args.rest(),
+    )
+    .await?
+    .send(ctx, msg)
+    .await
+}
+
+#[command("delete")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<name>"]
+#[example = "rules"]
+#[description = "Delete a tag you own, bot admins can delete any tag."]
+async fn tag_delete(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = single_name(lang, &mut args)?;
+    let member = msg.member(ctx).await?;
+
+    tag_delete_response(ctx, lang, msg.guild_id.unwrap(), &member, &name)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("info")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<name>"]
+#[example = "rules"]
+#[description = "Show who owns a tag and how often it was used."]
+async fn tag_info(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = single_name(lang, &mut args)?;
+
+    tag_info_response(ctx, lang, msg.guild_id.unwrap(), &name)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("list")]
+#[only_in(guilds)]
+#[usage = "[member]"]
+#[example = "@MrArkon"]
+#[description = "List the tags of this guild, or the tags a member owns."]
+async fn tag_list(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let owner = if args.is_empty() {
+        None
+    } else {
+        let member = <Member as ArgumentConvert>::convert(
+            ctx,
+            msg.guild_id,
+            Some(msg.channel_id),
+            args.message(),
+        )
+        .await
+        .map_err(|_| RusticError::MemberNotFound(args.message().to_string()))?;
+        Some(member.user)
+    };
+
+    tag_list_response(ctx, lang, msg.guild_id.unwrap(), owner.as_ref())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("search")]
+#[only_in(guilds)]
+#[min_args(1)]
+#[usage = "<text>"]
+#[example = "rule"]
+#[description = "Find tags with the text in their name."]
+async fn tag_search(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    tag_search_response(ctx, lang, msg.guild_id.unwrap(), args.message())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("raw")]
+#[only_in(guilds)]
+#[num_args(1)]
+#[usage = "<name>"]
+#[example = "rules"]
+#[description = "Show the content of a tag without filling in the variables."]
+async fn tag_raw(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = single_name(lang, &mut args)?;
+
+    tag_raw_response(ctx, lang, msg.guild_id.unwrap(), &name)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("transfer")]
+#[only_in(guilds)]
+#[num_args(2)]
+#[usage = "<name> <member>"]
+#[example = "rules @MrArkon"]
+#[description = "Give a tag you own to another member."]
+async fn tag_transfer(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let name = single_name(lang, &mut args)?;
+    let new_owner =
+        <Member as ArgumentConvert>::convert(ctx, msg.guild_id, Some(msg.channel_id), args.rest())
+            .await
+            .map_err(|_| RusticError::MemberNotFound(args.rest().to_string()))?;
+    let member = msg.member(ctx).await?;
+
+    tag_transfer_response(
+        ctx,
+        lang,
+        msg.guild_id.unwrap(),
+        &member,
+        &name,
+        &new_owner.user,
+    )
+    .await?
+    .send(ctx, msg)
+    .await
+}
@@ -0,0 +1,295 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::Response;
+use crate::{
+    error::RusticError,
+    i18n::{language, t, Lang},
+    timezones::{self, find_timezone, timezone},
+    utils::parse_when,
+    PgPoolContainer, TimezoneCacheContainer,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::prelude::{Message, UserId},
+    prelude::Context,
+    utils::parse_username,
+};
+
+fn format_time(time: DateTime<Utc>, tz: Tz) -> String {
+    time.with_timezone(&tz)
+        .format("%H:%M %Z (%Y-%m-%d)")
+        .to_string()
+}
+
+fn not_found(lang: Lang, text: &str, suggestions: &[Tz]) -> RusticError {
+    let mut message = t!(lang, "timezone-not-found", timezone = text.trim());
+
+    if !suggestions.is_empty() {
+        let timezones = suggestions
+            .iter()
+            .map(|timezone| format!("`{}`", timezone.name()))
+            .collect::<Vec<String>>()
+            .join(", ");
+        message.push(' ');
+        message.push_str(&t!(lang, "timezone-suggestions", timezones = timezones));
+    }
+
+    RusticError::BadArgument(message)
+}
+
+/// A timezone by name, or the timezone of a mentioned user.
+async fn resolve_zone(ctx: &Context, lang: Lang, text: &str) -> Result<Tz, RusticError> {
+    match parse_username(text.trim()) {
+        Some(user_id) => timezone(ctx, UserId(user_id)).await.ok_or_else(|| {
+            RusticError::BadArgument(t!(
+                lang,
+                "timezone-user-none",
+                user = format!("<@{}>", user_id)
+            ))
+        }),
+        None => find_timezone(text).map_err(|suggestions| not_found(lang, text, &suggestions)),
+    }
+}
+
+pub async fn timezone_show_response(
+    ctx: &Context,
+    lang: Lang,
+    author_id: UserId,
+    user_id: UserId,
+) -> Result<Response, RusticError> {
+    let user = format!("<@{}>", user_id.0);
+
+    Ok(Response::Content(
+        match (timezone(ctx, user_id).await, user_id == author_id) {
+            (Some(tz), true) => t!(
+                lang,
+                "timezone-current",
+                timezone = tz.name(),
+                time = format_time(Utc::now(), tz)
+            ),
+            (Some(tz), false) => t!(
+                lang,
+                "timezone-user-current",
+                user = user,
+                timezone = tz.name(),
+                time = format_time(Utc::now(), tz)
+            ),
+            (None, true) => t!(lang, "timezone-none"),
+            (None, false) => t!(lang, "timezone-user-none", user = user),
+        },
+    ))
+}
+
+/// Change the timezone of a user, `reset` removes it.
+pub async fn timezone_set_response(
+    ctx: &Context,
+    lang: Lang,
+    user_id: UserId,
+    text: &str,
+) -> Result<Response, RusticError> {
+    let new_timezone = if text.trim().eq_ignore_ascii_case("reset") {
+        None
+    } else {
+        Some(find_timezone(text).map_err(|suggestions| not_found(lang, text, &suggestions))?)
+    };
+
+    let (pool, timezone_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<TimezoneCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    timezones::set_timezone(&pool, user_id, new_timezone).await?;
+    timezone_cache.invalidate(user_id);
+
+    Ok(Response::Content(match new_timezone {
+        Some(tz) => t!(
+            lang,
+            "timezone-set",
+            timezone = tz.name(),
+            time = format_time(Utc::now(), tz)
+        ),
+        None => t!(lang, "timezone-reset"),
+    }))
+}
+
+/// Show the current time somewhere, or convert a time between timezones. Times are read in
+/// `from`, or the user's own timezone, and converted to `to`.
+pub async fn time_response(
+    ctx: &Context,
+    lang: Lang,
+    user_id: UserId,
+    when: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Response, RusticError> {
+    let own = timezone(ctx, user_id).await;
+    let from = match from {
+        Some(from) => Some(resolve_zone(ctx, lang, from).await?),
+        None => None,
+    };
+    let to = match to {
+        Some(to) => Some(resolve_zone(ctx, lang, to).await?),
+        None => None,
+    };
+
+    let when = match when {
+        Some(when) => when,
+        None => {
+            let tz = to.or(from).or(own).unwrap_or(Tz::UTC);
+            let mut content = t!(
+                lang,
+                "time-now",
+                timezone = tz.name(),
+                time = format_time(Utc::now(), tz)
+            );
+            if to.or(from).or(own).is_none() {
+                content.push('\n');
+                content.push_str(&t!(lang, "time-set-hint"));
+            }
+
+            return Ok(Response::Content(content));
+        }
+    };
+
+    let source = from.or(own).unwrap_or(Tz::UTC);
+    let words = when.split_whitespace().collect::<Vec<&str>>();
+    let time = match parse_when(&words, Utc::now(), &source) {
+        Some((time, used)) if used == words.len() => time,
+        _ => return Err(RusticError::BadArgument(t!(lang, "time-invalid"))),
+    };
+    // Discord shows timestamps in the local time of whoever reads them
+    let timestamp = format!("<t:{}:F>", time.timestamp());
+
+    // A time read in another timezone is converted to the user's own
+    let target = to.or_else(|| from.map(|_| own.unwrap_or(Tz::UTC)));
+
+    Ok(Response::Content(match target {
+        Some(target) => t!(
+            lang,
+            "time-converted",
+            time = format_time(time, source),
+            from = source.name(),
+            converted = format_time(time, target),
+            to = target.name(),
+            timestamp = timestamp
+        ),
+        None => t!(
+            lang,
+            "time-at",
+            time = format_time(time, source),
+            timezone = source.name(),
+            timestamp = timestamp
+        ),
+    }))
+}
+
+#[command("timezone")]
+#[aliases("tz")]
+#[usage = "[member]"]
+#[example = "@MrArkon"]
+#[sub_commands(timezone_set, timezone_reset)]
+#[description = "Show your timezone, or the timezone of another member. Times you give the bot, like in reminders, are read in your timezone."]
+async fn user_timezone(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let user_id = if args.is_empty() {
+        msg.author.id
+    } else {
+        parse_username(args.rest().trim())
+            .map(UserId)
+            .ok_or_else(|| RusticError::MemberNotFound(args.rest().to_string()))?
+    };
+
+    timezone_show_response(ctx, lang, msg.author.id, user_id)
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("set")]
+#[min_args(1)]
+#[usage = "<city or timezone>"]
+#[example = "Sao Paulo"]
+#[example = "Europe/London"]
+#[description = "Pick your timezone by the name of a big city near you or by its IANA name."]
+async fn timezone_set(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    timezone_set_response(ctx, lang, msg.author.id, args.rest())
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command("reset")]
+#[description = "Remove your timezone, times are read in UTC again."]
+async fn timezone_reset(ctx: &Context, msg: &Message) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    timezone_set_response(ctx, lang, msg.author.id, "reset")
+        .await?
+        .send(ctx, msg)
+        .await
+}
+
+#[command]
+#[usage = "[member] | [time] [timezone] [to <timezone>]"]
+#[example = "@MrArkon"]
+#[example = "Tokyo"]
+#[example = "9pm London to Tokyo"]
+#[example = "tomorrow 18:00 to @MrArkon"]
+#[description = "Show the current time for you, a member or in a timezone, or convert a time between timezones. A time without a timezone is read in yours."]
+async fn time(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let text = args.rest().trim();
+
+    if let Some(user_id) = parse_username(text) {
+        return timezone_show_response(ctx, lang, msg.author.id, UserId(user_id))
+            .await?
+            .send(ctx, msg)
+            .await;
+    }
+
+    let words = text.split_whitespace().collect::<Vec<&str>>();
+    let used = parse_when(&words, Utc::now(), &Utc).map_or(0, |(_, used)| used);
+    let (when, zones) = words.split_at(used);
+
+    let (from, to) = match zones
+        .iter()
+        .position(|word| word.eq_ignore_ascii_case("to") || word.eq_ignore_ascii_case("in"))
+    {
+        Some(position) => (&zones[..position], &zones[position + 1..]),
+        None => (zones, &[][..]),
+    };
+    let join = |words: &[&str]| (!words.is_empty()).then(|| words.join(" "));
+    let (when, from, to) = (join(when), join(from), join(to));
+
+    time_response(
+        ctx,
+        lang,
+        msg.author.id,
+        when.as_deref(),
+        from.as_deref(),
+        to.as_deref(),
+    )
+    .await?
+    .send(ctx, msg)
+    .await
+}
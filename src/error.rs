@@ -0,0 +1,356 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    i18n::{t, Lang},
+    pipeline,
+    theme::{Style, Theme},
+    ErrorLogContainer,
+};
+use dashmap::DashMap;
+use image::ImageError;
+use lazy_static::lazy_static;
+use serenity::{
+    builder::CreateEmbed,
+    framework::standard::CommandError,
+    http::AttachmentType,
+    model::prelude::{ChannelId, GuildId, UserId},
+    prelude::Context,
+};
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    borrow::Cow,
+    error::Error,
+    fmt,
+    time::{Duration, Instant},
+};
+use tracing::{error, info};
+
+/// How long the same internal error of a command isn't forwarded to the owner again.
+const REPORT_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+lazy_static! {
+    static ref REPORTED_ERRORS: DashMap<(String, String), Instant> = DashMap::new();
+}
+
+/// Errors returned by commands, split between mistakes the user can fix and
+/// internal errors which get reported to the bot owner.
+#[derive(Debug)]
+pub enum RusticError {
+    BadArgument(String),
+    MemberNotFound(String),
+    UpstreamUnavailable(String),
+    ImageDecode(ImageError),
+    /// Too many images are already being processed.
+    Busy,
+    TimedOut,
+    Internal(CommandError, Backtrace),
+}
+
+impl RusticError {
+    /// Wrap any error as an internal error, capturing a backtrace if `RUST_BACKTRACE` is set.
+    pub fn internal<E: Into<CommandError>>(error: E) -> Self {
+        RusticError::Internal(error.into(), Backtrace::capture())
+    }
+
+    /// Recover the typed error from a boxed command error, anything else is internal. Those
+    /// have no backtrace, one captured here would only point at the after hook.
+    pub fn from_command_error(error: CommandError) -> Self {
+        match error.downcast::<RusticError>() {
+            Ok(error) => *error,
+            Err(error) => RusticError::Internal(error, Backtrace::disabled()),
+        }
+    }
+
+    pub fn is_internal(&self) -> bool {
+        matches!(self, RusticError::Internal(..))
+    }
+
+    fn title(&self, lang: Lang) -> String {
+        match self {
+            RusticError::BadArgument(_) => t!(lang, "error-bad-argument"),
+            RusticError::MemberNotFound(_) => t!(lang, "error-member-not-found"),
+            RusticError::UpstreamUnavailable(_) => t!(lang, "error-upstream-unavailable"),
+            RusticError::ImageDecode(_) => t!(lang, "error-image-decode"),
+            RusticError::Busy => t!(lang, "error-busy"),
+            RusticError::TimedOut => t!(lang, "error-timed-out"),
+            RusticError::Internal(..) => t!(lang, "error-internal"),
+        }
+    }
+
+    /// Bad arguments are already translated when the error is created.
+    fn description(&self, lang: Lang) -> String {
+        match self {
+            RusticError::BadArgument(reason) => reason.clone(),
+            RusticError::MemberNotFound(member) => {
+                t!(
+                    lang,
+                    "error-member-not-found-description",
+                    member = member.as_str()
+                )
+            }
+            RusticError::UpstreamUnavailable(service) => t!(
+                lang,
+                "error-upstream-unavailable-description",
+                service = service.as_str()
+            ),
+            RusticError::ImageDecode(ImageError::Unsupported(_)) => {
+                t!(lang, "error-image-unsupported-description")
+            }
+            RusticError::ImageDecode(ImageError::Limits(_)) => t!(
+                lang,
+                "error-image-too-big-description",
+                max = pipeline::MAX_PIXELS / 1024 / 1024
+            ),
+            RusticError::ImageDecode(_) => t!(lang, "error-image-decode-description"),
+            RusticError::Busy => t!(lang, "error-busy-description"),
+            RusticError::TimedOut => t!(lang, "error-timed-out-description"),
+            RusticError::Internal(..) => t!(lang, "error-internal-description"),
+        }
+    }
+
+    /// The embed shown to the user who ran the command.
+    pub fn embed(&self, lang: Lang, theme: &Theme, error_id: &str) -> CreateEmbed {
+        let mut embed = theme.embed(Style::Error);
+        embed.title(format!(":warning: | {}", self.title(lang)));
+        embed.description(self.description(lang));
+        embed.footer(|f| f.text(t!(lang, "error-footer", error_id = error_id)));
+        embed
+    }
+}
+
+impl fmt::Display for RusticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RusticError::ImageDecode(why) => write!(f, "Couldn't decode image: {}", why),
+            RusticError::Internal(why, _) => write!(f, "{}", why),
+            _ => write!(f, "{}", self.description(Lang::default())),
+        }
+    }
+}
+
+impl Error for RusticError {}
+
+/// Only connection problems, timeouts and server errors mean the service is down, anything
+/// else like a response that can't be decoded is a bug.
+impl From<reqwest::Error> for RusticError {
+    fn from(error: reqwest::Error) -> Self {
+        let unavailable = error.is_connect()
+            || error.is_timeout()
+            || error
+                .status()
+                .is_some_and(|status| status.is_server_error());
+        if !unavailable {
+            return RusticError::internal(error);
+        }
+
+        let service = error
+            .url()
+            .and_then(|url| url.host_str())
+            .unwrap_or("An external service")
+            .to_string();
+        RusticError::UpstreamUnavailable(service)
+    }
+}
+
+impl From<ImageError> for RusticError {
+    fn from(error: ImageError) -> Self {
+        RusticError::ImageDecode(error)
+    }
+}
+
+impl From<sqlx::Error> for RusticError {
+    fn from(error: sqlx::Error) -> Self {
+        RusticError::internal(error)
+    }
+}
+
+impl From<serenity::Error> for RusticError {
+    fn from(error: serenity::Error) -> Self {
+        RusticError::internal(error)
+    }
+}
+
+/// Where and by whom a failing command was invoked.
+pub struct ErrorContext {
+    pub command: String,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub user_id: UserId,
+    pub args: String,
+}
+
+/// Returns true if the same error of the same command was forwarded recently, so an outage
+/// doesn't turn every invocation into a message to the owner.
+fn is_repeated(command: &str, error: &RusticError) -> bool {
+    let now = Instant::now();
+    let key = (command.to_string(), error.to_string());
+
+    if let Some(last) = REPORTED_ERRORS.get(&key) {
+        if now.duration_since(*last) < REPORT_COOLDOWN {
+            return true;
+        }
+    }
+
+    REPORTED_ERRORS.retain(|_, last| now.duration_since(*last) < REPORT_COOLDOWN);
+    REPORTED_ERRORS.insert(key, now);
+
+    false
+}
+
+/// Log an error and forward internal errors to the owner, returning the error ID shown to the user.
+pub async fn report(ctx: &Context, error: &RusticError, context: &ErrorContext) -> String {
+    let error_id = format!("{:08X}", rand::random::<u32>());
+
+    if !error.is_internal() {
+        info!(
+            error_id = %error_id,
+            shard = ctx.shard_id,
+            guild = context.guild_id.map_or(0, |id| id.0),
+            channel = context.channel_id.0,
+            user = context.user_id.0,
+            command = %context.command,
+            "Command failed: {}",
+            error
+        );
+        return error_id;
+    }
+
+    let details = match error {
+        RusticError::Internal(why, _) => format!("{:?}", why),
+        _ => format!("{:?}", error),
+    };
+
+    error!(
+        error_id = %error_id,
+        shard = ctx.shard_id,
+        guild = context.guild_id.map_or(0, |id| id.0),
+        channel = context.channel_id.0,
+        user = context.user_id.0,
+        command = %context.command,
+        "Command returned an error: {}",
+        details
+    );
+
+    if is_repeated(&context.command, error) {
+        return error_id;
+    }
+
+    let log_channel = ctx
+        .data
+        .read()
+        .await
+        .get::<ErrorLogContainer>()
+        .cloned()
+        .flatten();
+
+    let channel_id = match log_channel {
+        Some(channel_id) => channel_id,
+        None => match owner_dm(ctx).await {
+            Some(channel_id) => channel_id,
+            None => return error_id,
+        },
+    };
+
+    let backtrace = match error {
+        RusticError::Internal(_, backtrace) if backtrace.status() == BacktraceStatus::Captured => {
+            backtrace.to_string()
+        }
+        _ => String::new(),
+    };
+
+    let mut embed = Theme::default().embed(Style::Error);
+    embed.title(format!(":warning: | Error {}", error_id));
+    embed.description(format!("```rust\n{}```", truncate(&details, 1000)));
+    embed.fields(vec![
+        ("Command", context.command.clone(), true),
+        (
+            "Guild",
+            context
+                .guild_id
+                .map_or_else(|| "DM".to_string(), |id| id.0.to_string()),
+            true,
+        ),
+        ("Channel", context.channel_id.0.to_string(), true),
+        ("User", context.user_id.0.to_string(), true),
+    ]);
+    if !context.args.is_empty() {
+        embed.field(
+            "Arguments",
+            format!("```{}```", truncate(&context.args, 1000)),
+            false,
+        );
+    }
+
+    let result = channel_id
+        .send_message(ctx, |message| {
+            message.set_embed(embed);
+            if !backtrace.is_empty() {
+                message.add_file(AttachmentType::Bytes {
+                    data: Cow::from(backtrace.into_bytes()),
+                    filename: format!("{}.txt", error_id),
+                });
+            }
+            message
+        })
+        .await;
+
+    if let Err(why) = result {
+        error!(
+            "Couldn't forward error {} to the owner: {:?}",
+            error_id, why
+        );
+    }
+
+    error_id
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+async fn owner_dm(ctx: &Context) -> Option<ChannelId> {
+    let owner = match ctx.http.get_current_application_info().await {
+        Ok(info) => info.owner,
+        Err(why) => {
+            error!("Couldn't get the application owner: {:?}", why);
+            return None;
+        }
+    };
+
+    match owner.create_dm_channel(ctx).await {
+        Ok(channel) => Some(channel.id),
+        Err(why) => {
+            error!("Couldn't open a DM with the application owner: {:?}", why);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwards_each_error_once_per_cooldown() {
+        let error = RusticError::internal("database is down");
+        let other = RusticError::internal("something else");
+
+        assert!(!is_repeated("test-report", &error));
+        assert!(is_repeated("test-report", &error));
+        assert!(!is_repeated("test-report", &other));
+        assert!(!is_repeated("test-report-other", &error));
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The `StandardFramework` can't be reconfigured once the client owns it, so it is
+//! rebuilt with the current blocklist whenever that changes. Ratelimits are kept
+//! outside of the framework, nothing is lost by replacing it. Guild aliases are
+//! expanded here too, before the framework looks up the command.
+
+use crate::{
+    aliases,
+    blocklist::{self, Blocked},
+};
+use chrono::{DateTime, Utc};
+use serenity::{
+    async_trait,
+    framework::{standard::StandardFramework, Framework},
+    model::{
+        channel::Message,
+        id::{GuildId, UserId},
+    },
+    prelude::{Context, RwLock},
+};
+use sqlx::PgPool;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+type Build = Box<dyn Fn(HashSet<UserId>, HashSet<GuildId>) -> StandardFramework + Send + Sync>;
+
+struct Inner {
+    build: Build,
+    framework: RwLock<Arc<StandardFramework>>,
+    blocked_users: Mutex<HashSet<UserId>>,
+    blocked_guilds: Mutex<HashSet<GuildId>>,
+    next_expiry: Mutex<Option<DateTime<Utc>>>,
+}
+
+/// Dispatches messages to a `StandardFramework` configured with the blocklist.
+#[derive(Clone)]
+pub struct RusticFramework {
+    inner: Arc<Inner>,
+}
+
+impl RusticFramework {
+    pub fn new<F>(build: F) -> Self
+    where
+        F: Fn(HashSet<UserId>, HashSet<GuildId>) -> StandardFramework + Send + Sync + 'static,
+    {
+        let framework = build(HashSet::new(), HashSet::new());
+
+        RusticFramework {
+            inner: Arc::new(Inner {
+                build: Box::new(build),
+                framework: RwLock::new(Arc::new(framework)),
+                blocked_users: Mutex::new(HashSet::new()),
+                blocked_guilds: Mutex::new(HashSet::new()),
+                next_expiry: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Reload the blocklist from the database and rebuild the framework with it.
+    pub async fn sync(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let Blocked {
+            users,
+            guilds,
+            next_expiry,
+        } = blocklist::active(pool).await?;
+
+        *self.inner.blocked_users.lock().unwrap() = users.clone();
+        *self.inner.blocked_guilds.lock().unwrap() = guilds.clone();
+        *self.inner.next_expiry.lock().unwrap() = next_expiry;
+
+        let framework = (self.inner.build)(users, guilds);
+        *self.inner.framework.write().await = Arc::new(framework);
+
+        Ok(())
+    }
+
+    /// Returns true once a block has run out and the framework still enforces it.
+    pub fn has_expired_blocks(&self) -> bool {
+        self.inner
+            .next_expiry
+            .lock()
+            .unwrap()
+            .is_some_and(|expiry| expiry <= Utc::now())
+    }
+
+    pub fn is_user_blocked(&self, user_id: UserId) -> bool {
+        self.inner.blocked_users.lock().unwrap().contains(&user_id)
+    }
+
+    pub fn is_guild_blocked(&self, guild_id: GuildId) -> bool {
+        self.inner
+            .blocked_guilds
+            .lock()
+            .unwrap()
+            .contains(&guild_id)
+    }
+}
+
+#[async_trait]
+impl Framework for RusticFramework {
+    async fn dispatch(&self, ctx: Context, mut msg: Message) {
+        aliases::expand(&ctx, &mut msg).await;
+
+        let framework = self.inner.framework.read().await.clone();
+        framework.dispatch(ctx, msg).await;
+    }
+}
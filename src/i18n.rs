@@ -0,0 +1,355 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Translations of the bot's responses. The Fluent bundles in `locales/` are built into
+//! the binary, anything missing from a language falls back to English. Owner commands
+//! are only used by the bot's operators and aren't translated.
+
+use crate::{LanguageCacheContainer, PgPoolContainer};
+use dashmap::DashMap;
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use lazy_static::lazy_static;
+use serenity::{
+    model::id::{GuildId, UserId},
+    prelude::Context,
+};
+use sqlx::{query, PgPool};
+use std::collections::HashMap;
+use tracing::error;
+use unic_langid::LanguageIdentifier;
+
+/// Every supported language as code, name and translations, the first one is the fallback.
+pub const LANGUAGES: &[(&str, &str, &str)] = &[
+    (
+        "en-US",
+        "English",
+        include_str!("../locales/en-US/rustic.ftl"),
+    ),
+    (
+        "es-ES",
+        "Español",
+        include_str!("../locales/es-ES/rustic.ftl"),
+    ),
+    (
+        "pt-BR",
+        "Português",
+        include_str!("../locales/pt-BR/rustic.ftl"),
+    ),
+];
+
+lazy_static! {
+    static ref BUNDLES: HashMap<&'static str, FluentBundle<FluentResource>> = LANGUAGES
+        .iter()
+        .map(|(code, _, source)| (*code, bundle(code, source)))
+        .collect();
+}
+
+fn bundle(code: &str, source: &str) -> FluentBundle<FluentResource> {
+    let resource = FluentResource::try_new(source.to_string())
+        .unwrap_or_else(|(_, errors)| panic!("Invalid translations for {}: {:?}", code, errors));
+    let language = code
+        .parse::<LanguageIdentifier>()
+        .expect("Invalid language code");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // Discord would show the isolation marks around every variable
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|errors| panic!("Duplicate translations for {}: {:?}", code, errors));
+
+    bundle
+}
+
+/// Format a translated message, with optional `name = value` arguments.
+macro_rules! t {
+    ($lang:expr, $id:expr) => {
+        $lang.tr($id, None)
+    };
+    ($lang:expr, $id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $lang.tr($id, Some(&args))
+    }};
+}
+
+pub(crate) use t;
+
+/// One of the supported languages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lang(&'static str);
+
+impl Default for Lang {
+    fn default() -> Self {
+        Lang(LANGUAGES[0].0)
+    }
+}
+
+impl Lang {
+    /// Find a supported language by its code, the language part of it or its name.
+    pub fn parse(text: &str) -> Option<Lang> {
+        LANGUAGES
+            .iter()
+            .find(|(code, name, _)| {
+                code.eq_ignore_ascii_case(text)
+                    || code
+                        .split('-')
+                        .next()
+                        .is_some_and(|language| language.eq_ignore_ascii_case(text))
+                    || name.to_lowercase() == text.to_lowercase()
+            })
+            .map(|(code, _, _)| Lang(code))
+    }
+
+    pub fn code(self) -> &'static str {
+        self.0
+    }
+
+    pub fn name(self) -> &'static str {
+        LANGUAGES
+            .iter()
+            .find(|(code, _, _)| *code == self.0)
+            .map_or(self.0, |(_, name, _)| name)
+    }
+
+    /// Translate a message, use the `t!` macro to pass arguments.
+    pub fn tr(self, id: &str, args: Option<&FluentArgs>) -> String {
+        for code in [self.0, Lang::default().0] {
+            let bundle = &BUNDLES[code];
+
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = Vec::new();
+                let text = bundle.format_pattern(pattern, args, &mut errors);
+
+                if !errors.is_empty() {
+                    error!("Couldn't format {} in {}: {:?}", id, code, errors);
+                }
+
+                return text.into_owned();
+            }
+        }
+
+        error!("Missing translation for {}", id);
+        id.to_string()
+    }
+}
+
+/// Per-guild and per-user cache of the chosen languages, like the prefix cache.
+#[derive(Default)]
+pub struct LanguageCache {
+    guilds: DashMap<GuildId, Option<Lang>>,
+    users: DashMap<UserId, Option<Lang>>,
+}
+
+impl LanguageCache {
+    pub async fn guild(&self, pool: &PgPool, guild_id: GuildId) -> Option<Lang> {
+        if let Some(language) = self.guilds.get(&guild_id) {
+            return *language;
+        }
+
+        match query!(
+            "SELECT language FROM guilds WHERE guild_id=$1",
+            guild_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await
+        {
+            Ok(row) => {
+                let language = row
+                    .and_then(|row| row.language)
+                    .and_then(|code| Lang::parse(&code));
+                self.guilds.insert(guild_id, language);
+                language
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next command retries the database
+                error!("Couldn't query database for the guild language: {}", why);
+                None
+            }
+        }
+    }
+
+    pub async fn user(&self, pool: &PgPool, user_id: UserId) -> Option<Lang> {
+        if let Some(language) = self.users.get(&user_id) {
+            return *language;
+        }
+
+        match query!(
+            "SELECT language FROM users WHERE user_id=$1",
+            user_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await
+        {
+            Ok(row) => {
+                let language = row
+                    .and_then(|row| row.language)
+                    .and_then(|code| Lang::parse(&code));
+                self.users.insert(user_id, language);
+                language
+            }
+            Err(why) => {
+                error!("Couldn't query database for the user language: {}", why);
+                None
+            }
+        }
+    }
+
+    pub fn invalidate_guild(&self, guild_id: GuildId) {
+        self.guilds.remove(&guild_id);
+    }
+
+    pub fn invalidate_user(&self, user_id: UserId) {
+        self.users.remove(&user_id);
+    }
+
+    /// The language to answer a user in: their own choice, the guild's, or English.
+    pub async fn resolve(&self, pool: &PgPool, guild_id: Option<GuildId>, user_id: UserId) -> Lang {
+        if let Some(language) = self.user(pool, user_id).await {
+            return language;
+        }
+
+        match guild_id {
+            Some(guild_id) => self.guild(pool, guild_id).await.unwrap_or_default(),
+            None => Lang::default(),
+        }
+    }
+}
+
+/// The language to answer a user in, see [`LanguageCache::resolve`].
+pub async fn language(ctx: &Context, guild_id: Option<GuildId>, user_id: UserId) -> Lang {
+    let (pool, language_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<LanguageCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    language_cache.resolve(&pool, guild_id, user_id).await
+}
+
+pub async fn set_guild_language(
+    pool: &PgPool,
+    guild_id: GuildId,
+    language: Option<Lang>,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO guilds (guild_id, language) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET language = $2",
+        guild_id.0 as i64,
+        language.map(Lang::code)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_user_language(
+    pool: &PgPool,
+    user_id: UserId,
+    language: Option<Lang>,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO users (user_id, language) VALUES ($1, $2) ON CONFLICT (user_id) DO UPDATE SET language = $2",
+        user_id.0 as i64,
+        language.map(Lang::code)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// The message ids of a Fluent file with the variables each message uses.
+    fn messages(source: &str) -> HashMap<String, BTreeSet<String>> {
+        let mut messages = HashMap::new();
+        let mut current = None;
+
+        for line in source.lines() {
+            if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if let Some((id, _)) = line.split_once('=') {
+                    current = Some(id.trim().to_string());
+                    messages.insert(id.trim().to_string(), BTreeSet::new());
+                }
+            } else if !line.starts_with(' ') {
+                current = None;
+            }
+
+            if let Some(id) = &current {
+                for variable in line.split('$').skip(1) {
+                    let name = variable
+                        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-')
+                        .next()
+                        .unwrap_or_default();
+                    messages.get_mut(id).unwrap().insert(name.to_string());
+                }
+            }
+        }
+
+        messages
+    }
+
+    #[test]
+    fn every_message_is_translated() {
+        let (_, _, english) = LANGUAGES[0];
+        let english = messages(english);
+        assert!(!english.is_empty());
+
+        for (code, _, source) in &LANGUAGES[1..] {
+            let translated = messages(source);
+
+            for (id, variables) in &english {
+                match translated.get(id) {
+                    Some(translated) => assert_eq!(
+                        variables, translated,
+                        "{} uses different variables in {}",
+                        id, code
+                    ),
+                    None => panic!("{} is missing from {}", id, code),
+                }
+            }
+
+            for id in translated.keys() {
+                assert!(
+                    english.contains_key(id),
+                    "{} in {} isn't in English",
+                    id,
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_bundle_loads() {
+        for (code, _, _) in LANGUAGES {
+            assert!(BUNDLES.contains_key(code));
+        }
+    }
+
+    #[test]
+    fn parses_language_names() {
+        assert_eq!(Lang::parse("pt"), Some(Lang("pt-BR")));
+        assert_eq!(Lang::parse("ES-es"), Some(Lang("es-ES")));
+        assert_eq!(Lang::parse("español"), Some(Lang("es-ES")));
+        assert_eq!(Lang::parse("klingon"), None);
+    }
+}
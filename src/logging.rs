@@ -0,0 +1,83 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::settings::{LogFormat, LogRotation, LoggingSettings};
+use std::path::Path;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+/// Swaps the filter of the installed logger, used to apply new settings at runtime.
+pub type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Build the filter from the global level and the per-module filters.
+pub fn filter(settings: &LoggingSettings) -> EnvFilter {
+    let mut filter = EnvFilter::new(settings.level.to_string());
+    for (path, level) in &settings.filters {
+        match format!("{}={}", path, level).parse() {
+            Ok(directive) => filter = filter.add_directive(directive),
+            Err(why) => eprintln!("Ignoring invalid log filter '{}': {}", path, why),
+        }
+    }
+    filter
+}
+
+/// Install the global logger, the returned guard flushes the log file when dropped.
+pub fn init(settings: &LoggingSettings) -> (Option<WorkerGuard>, FilterHandle) {
+    let (file_writer, guard) = match &settings.file {
+        Some(path) => {
+            let directory = path.parent().unwrap_or_else(|| Path::new("."));
+            let file_name = path.file_name().unwrap_or_else(|| "rustic.log".as_ref());
+            let rotation = match settings.rotation {
+                LogRotation::Minutely => Rotation::MINUTELY,
+                LogRotation::Hourly => Rotation::HOURLY,
+                LogRotation::Daily => Rotation::DAILY,
+                LogRotation::Never => Rotation::NEVER,
+            };
+
+            let (writer, guard) = tracing_appender::non_blocking(RollingFileAppender::new(
+                rotation, directory, file_name,
+            ));
+            (Some(writer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let json = settings.format == LogFormat::Json;
+    let (filter_layer, handle) = reload::Layer::new(filter(settings));
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with((!json).then(fmt::layer))
+        .with(json.then(|| fmt::layer().json().flatten_event(true)))
+        .with(
+            file_writer
+                .clone()
+                .filter(|_| !json)
+                .map(|writer| fmt::layer().with_ansi(false).with_writer(writer)),
+        )
+        .with(
+            file_writer
+                .filter(|_| json)
+                .map(|writer| fmt::layer().json().flatten_event(true).with_writer(writer)),
+        )
+        .init();
+
+    (guard, handle)
+}
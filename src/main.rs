@@ -13,38 +13,94 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod aliases;
+mod blocklist;
+mod cache;
+mod checks;
 mod commands;
+mod error;
+mod framework;
+mod i18n;
+mod logging;
+mod pipeline;
+mod presence;
+mod ratelimit;
+mod reload;
+mod reminders;
+mod rules;
 mod settings;
-
-use commands::{config::*, fun::*, image::*, misc::*};
-use log::{error, info};
-use pretty_env_logger::formatted_builder;
+mod tags;
+mod theme;
+mod timezones;
+mod utils;
+
+use aliases::AliasCache;
+use cache::PrefixCache;
+use checks::*;
+use commands::{config::*, fun::*, image::*, misc::*, owner::*, reminders::*, tags::*, time::*};
+use dashmap::DashMap;
+use error::{report, ErrorContext, RusticError};
+use framework::RusticFramework;
+use i18n::{language, t, Lang, LanguageCache};
+use lazy_static::lazy_static;
+use logging::FilterHandle;
+use pipeline::ImageWorkers;
+use presence::Presence;
+use ratelimit::{Invocation, Ratelimit, Ratelimiter};
+use reminders::Scheduler;
 use reqwest::Client as ReqwestClient;
+use rules::RuleCache;
 use serenity::{
     async_trait,
     client::bridge::gateway::ShardManager,
     framework::standard::{
         help_commands,
         macros::{group, help, hook},
-        Args, CommandGroup, CommandResult, DispatchError, HelpOptions, StandardFramework,
+        Args, Command, CommandGroup, CommandResult, Delimiter, DispatchError, HelpOptions, Reason,
+        StandardFramework,
     },
     http::Http,
     model::{
-        channel::Message,
+        channel::{Channel, Message},
         event::ResumedEvent,
         gateway::Ready,
+        guild::Guild,
         id::UserId,
-        prelude::{Activity, GuildId},
+        interactions::Interaction,
+        prelude::{ChannelId, GuildId},
     },
     prelude::{Client, Context, EventHandler, TypeMapKey},
+    utils::Colour,
+};
+use settings::Settings;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::{
+    collections::HashSet,
+    error::Error,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use sqlx::{postgres::PgPoolOptions, query, PgPool};
-use std::{collections::HashSet, error::Error, sync::Arc};
+use theme::{theme, Style, ThemeCache};
+use timezones::TimezoneCache;
 use tokio::sync::Mutex;
+use tracing::{debug, error, info};
 
 struct ShardManagerContainer;
 struct ReqwestContainer;
 struct PgPoolContainer;
+struct PrefixCacheContainer;
+struct ErrorLogContainer;
+struct RatelimiterContainer;
+struct LogFilterContainer;
+struct PresenceContainer;
+struct FrameworkContainer;
+struct RuleCacheContainer;
+struct AliasCacheContainer;
+struct LanguageCacheContainer;
+struct ThemeCacheContainer;
+struct TimezoneCacheContainer;
+struct ImageWorkersContainer;
+struct SchedulerContainer;
 
 impl TypeMapKey for ShardManagerContainer {
     type Value = Arc<Mutex<ShardManager>>;
@@ -58,12 +114,84 @@ impl TypeMapKey for PgPoolContainer {
     type Value = PgPool;
 }
 
-struct Handler;
+impl TypeMapKey for PrefixCacheContainer {
+    type Value = Arc<PrefixCache>;
+}
+
+impl TypeMapKey for ErrorLogContainer {
+    type Value = Option<ChannelId>;
+}
+
+impl TypeMapKey for RatelimiterContainer {
+    type Value = Arc<Ratelimiter>;
+}
+
+impl TypeMapKey for LogFilterContainer {
+    type Value = FilterHandle;
+}
+
+impl TypeMapKey for PresenceContainer {
+    type Value = Arc<Presence>;
+}
+
+impl TypeMapKey for FrameworkContainer {
+    type Value = RusticFramework;
+}
+
+impl TypeMapKey for RuleCacheContainer {
+    type Value = Arc<RuleCache>;
+}
+
+impl TypeMapKey for AliasCacheContainer {
+    type Value = Arc<AliasCache>;
+}
+
+impl TypeMapKey for LanguageCacheContainer {
+    type Value = Arc<LanguageCache>;
+}
+
+impl TypeMapKey for ThemeCacheContainer {
+    type Value = Arc<ThemeCache>;
+}
+
+impl TypeMapKey for TimezoneCacheContainer {
+    type Value = Arc<TimezoneCache>;
+}
+
+impl TypeMapKey for ImageWorkersContainer {
+    type Value = Arc<ImageWorkers>;
+}
+
+impl TypeMapKey for SchedulerContainer {
+    type Value = Arc<Scheduler>;
+}
+
+struct Handler {
+    prewarm_prefix_cache: bool,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
-    async fn cache_ready(&self, _: Context, guilds: Vec<GuildId>) {
+    async fn cache_ready(&self, ctx: Context, guilds: Vec<GuildId>) {
         info!("Connected to {} guilds.", guilds.len());
+
+        if self.prewarm_prefix_cache {
+            let (pool, prefix_cache) = {
+                let data = ctx.data.read().await;
+                (
+                    data.get::<PgPoolContainer>().unwrap().clone(),
+                    data.get::<PrefixCacheContainer>().unwrap().clone(),
+                )
+            };
+
+            match prefix_cache.prewarm(&pool, &guilds).await {
+                Ok(()) => info!(
+                    "Pre-warmed the prefix cache with {} guilds.",
+                    prefix_cache.cached_guilds()
+                ),
+                Err(why) => error!("Couldn't pre-warm the prefix cache: {}", why),
+            }
+        }
     }
 
     async fn ready(&self, ctx: Context, ready: Ready) {
@@ -82,7 +210,43 @@ impl EventHandler for Handler {
             );
         }
 
-        ctx.set_activity(Activity::listening("@Rustic help")).await;
+        let presence = {
+            let data = ctx.data.read().await;
+            data.get::<PresenceContainer>().unwrap().clone()
+        };
+        presence.start(ctx.clone());
+
+        // Global commands only need to be registered once, not by every shard
+        if ready.shard.is_none_or(|shard| shard[0] == 0) {
+            if let Err(why) = commands::slash::register(&ctx).await {
+                error!("Couldn't register slash commands: {:?}", why);
+            }
+        }
+    }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild, _: bool) {
+        let framework = {
+            let data = ctx.data.read().await;
+            data.get::<FrameworkContainer>().unwrap().clone()
+        };
+
+        if framework.is_guild_blocked(guild.id) {
+            info!("Leaving blocked guild {} ({})", guild.name, guild.id);
+            if let Err(why) = guild.id.leave(&ctx.http).await {
+                error!("Couldn't leave blocked guild {}: {:?}", guild.id, why);
+            }
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::ApplicationCommand(command) = interaction {
+            if let Err(why) = commands::slash::dispatch(&ctx, &command).await {
+                error!(
+                    "Slash command '{}' returned an error {:?}",
+                    command.data.name, why
+                );
+            }
+        }
     }
 
     async fn resume(&self, _: Context, _: ResumedEvent) {
@@ -91,7 +255,7 @@ impl EventHandler for Handler {
 }
 
 #[group]
-#[commands(ping, about)]
+#[commands(ping, about, mylanguage, user_timezone, time)]
 struct Misc;
 
 #[group]
@@ -103,9 +267,35 @@ struct Fun;
 struct Image;
 
 #[group]
-#[commands(prefix)]
+#[only_in(guilds)]
+#[commands(tag)]
+struct Tags;
+
+#[group]
+#[commands(remind)]
+struct Reminders;
+
+#[group]
+#[checks(GuildAdmin)]
+#[commands(prefix, adminrole, commands, alias, guild_language, guild_theme)]
 struct Configuration;
 
+#[group]
+#[owners_only]
+#[commands(
+    shutdown,
+    restart_shard,
+    guilds,
+    leave,
+    setavatar,
+    setname,
+    sql,
+    reload_config,
+    presence,
+    blocklist
+)]
+struct Owner;
+
 #[help]
 #[max_levenshtein_distance(2)]
 async fn bot_help(
@@ -116,96 +306,568 @@ async fn bot_help(
     groups: &[&'static CommandGroup],
     owners: HashSet<UserId>,
 ) -> CommandResult {
-    let ho = help_options.clone();
-    let _ = help_commands::with_embeds(ctx, msg, args, &ho, groups, owners).await;
+    let mut ho = help_options.clone();
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let theme = theme(ctx, msg.guild_id).await;
+    ho.embed_success_colour = Colour::new(theme.primary);
+    ho.embed_error_colour = Colour::new(theme.error);
+
+    let aliases = match msg.guild_id {
+        Some(guild_id) => {
+            let (pool, alias_cache) = {
+                let data = ctx.data.read().await;
+                (
+                    data.get::<PgPoolContainer>().unwrap().clone(),
+                    data.get::<AliasCacheContainer>().unwrap().clone(),
+                )
+            };
+            alias_cache.get(&pool, guild_id).await
+        }
+        None => Default::default(),
+    };
+
+    // Show the help of the command a guild alias stands for
+    let args = match aliases.get(&args.message().to_lowercase()) {
+        Some(command) => Args::new(command, &[Delimiter::Single(' ')]),
+        None => args,
+    };
+    let command = find_command(args.message()).map(|(_, command)| command.options.names[0]);
+
+    let _ = help_commands::with_embeds(ctx, msg, args.clone(), &ho, groups, owners).await;
+
+    let listing = aliases
+        .iter()
+        .filter(|(_, target)| args.is_empty() || command == Some(target.as_str()))
+        .map(|(alias, target)| format!("`{}` → `{}`", alias, target))
+        .collect::<Vec<String>>();
+
+    if !listing.is_empty() {
+        msg.channel_id
+            .send_message(ctx, |message| {
+                let mut embed = theme.embed(Style::Primary);
+                embed.title(t!(lang, "help-aliases"));
+                embed.description(listing.join("\n"));
+                message.set_embed(embed)
+            })
+            .await?;
+    }
+
     Ok(())
 }
 
+#[hook]
+async fn before(ctx: &Context, msg: &Message, name: &str) -> bool {
+    debug!(
+        shard = ctx.shard_id,
+        guild = msg.guild_id.map_or(0, |id| id.0),
+        channel = msg.channel_id.0,
+        user = msg.author.id.0,
+        command = name,
+        "Running command"
+    );
+
+    // Subcommands are enabled and disabled along with their parent command
+    let (name, bucket) = match invoked_command(ctx, msg).await {
+        Some(invoked) => (
+            invoked.parent.options.names[0],
+            invoked.command.options.bucket,
+        ),
+        None => (name, None),
+    };
+    check_message(ctx, msg, name, bucket).await
+}
+
+/// Run the checks shared with slash commands for a message, telling the author why it was refused.
+async fn check_message(ctx: &Context, msg: &Message, name: &str, bucket: Option<&str>) -> bool {
+    let invocation = Invocation {
+        user_id: msg.author.id,
+        channel_id: msg.channel_id,
+        guild_id: msg.guild_id,
+        category_id: category_of(ctx, msg.channel_id).await,
+        roles: msg
+            .member
+            .as_ref()
+            .map(|member| member.roles.clone())
+            .unwrap_or_default(),
+    };
+
+    match check_invocation(ctx, name, bucket, &invocation).await {
+        Ok(()) => true,
+        Err(refusal) => {
+            if refusal.should_notify() && !is_suppressed(msg.author.id, refusal.kind()) {
+                let lang = language(ctx, msg.guild_id, msg.author.id).await;
+                let _ = msg.channel_id.say(&ctx.http, refusal.message(lang)).await;
+            }
+            false
+        }
+    }
+}
+
+/// Why a command was refused before it could run.
+enum Refusal {
+    Blocked,
+    Disabled(String),
+    DisabledHere { name: String, silent: bool },
+    Ratelimited { wait: Duration, first_try: bool },
+}
+
+impl Refusal {
+    fn kind(&self) -> &'static str {
+        match self {
+            Refusal::Blocked => "blocked",
+            Refusal::Disabled(_) => "command_disabled",
+            Refusal::DisabledHere { .. } => "command_disabled_here",
+            Refusal::Ratelimited { .. } => "ratelimited",
+        }
+    }
+
+    /// Only the first ratelimited attempt gets a response.
+    fn should_notify(&self) -> bool {
+        match self {
+            Refusal::Blocked | Refusal::Disabled(_) => true,
+            Refusal::DisabledHere { silent, .. } => !*silent,
+            Refusal::Ratelimited { first_try, .. } => *first_try,
+        }
+    }
+
+    fn message(&self, lang: Lang) -> String {
+        match self {
+            Refusal::Blocked => t!(lang, "refusal-blocked"),
+            Refusal::Disabled(name) => t!(lang, "refusal-disabled", name = name.as_str()),
+            Refusal::DisabledHere { name, .. } => {
+                t!(lang, "refusal-disabled-here", name = name.as_str())
+            }
+            Refusal::Ratelimited { wait, .. } => {
+                t!(lang, "refusal-ratelimited", seconds = wait.as_secs().max(1))
+            }
+        }
+    }
+}
+
+/// The category of a guild channel, if it's cached.
+async fn category_of(ctx: &Context, channel_id: ChannelId) -> Option<ChannelId> {
+    match channel_id.to_channel_cached(&ctx.cache).await {
+        Some(Channel::Guild(channel)) => channel.category_id,
+        _ => None,
+    }
+}
+
+/// Apply the blocklist, the disabled commands, the guild's command rules and the ratelimit
+/// buckets, shared by prefix and slash commands. Waits if the bucket allows delaying the command.
+async fn check_invocation(
+    ctx: &Context,
+    name: &str,
+    bucket: Option<&str>,
+    invocation: &Invocation,
+) -> Result<(), Refusal> {
+    let (settings, ratelimiter, framework, pool, rule_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<Settings>().unwrap().clone(),
+            data.get::<RatelimiterContainer>().unwrap().clone(),
+            data.get::<FrameworkContainer>().unwrap().clone(),
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<RuleCacheContainer>().unwrap().clone(),
+        )
+    };
+    let (name, group) = match find_command(name) {
+        Some((group, command)) => (command.options.names[0], group.name),
+        None => (name, ""),
+    };
+
+    // Prefix commands are already stopped by the framework, slash commands aren't
+    if framework.is_user_blocked(invocation.user_id)
+        || invocation
+            .guild_id
+            .is_some_and(|guild_id| framework.is_guild_blocked(guild_id))
+    {
+        return Err(Refusal::Blocked);
+    }
+
+    if settings.lock().unwrap().features.is_disabled(name) {
+        return Err(Refusal::Disabled(name.to_string()));
+    }
+
+    if let Some(guild_id) = invocation.guild_id {
+        let rules = rule_cache.get(&pool, guild_id).await;
+
+        if !rules.is_enabled(
+            &name.to_lowercase(),
+            &group.to_lowercase(),
+            invocation.channel_id,
+            &invocation.roles,
+        ) {
+            return Err(Refusal::DisabledHere {
+                name: name.to_string(),
+                silent: rules.silent,
+            });
+        }
+    }
+
+    let bucket = match bucket {
+        Some(bucket) => bucket,
+        None => return Ok(()),
+    };
+
+    match ratelimiter.hit(ctx, bucket, invocation).await {
+        Ratelimit::Allowed => Ok(()),
+        Ratelimit::Wait(wait) => {
+            tokio::time::sleep(wait).await;
+            ratelimiter.finish_waiting(bucket, invocation);
+            Ok(())
+        }
+        Ratelimit::Limited { wait, first_try } => Err(Refusal::Ratelimited { wait, first_try }),
+    }
+}
+
+/// The bucket of a top level command.
+fn command_bucket(name: &str) -> Option<&'static str> {
+    find_command(name).and_then(|(_, command)| command.options.bucket)
+}
+
 #[hook]
 async fn after(ctx: &Context, msg: &Message, name: &str, result: CommandResult) {
-    match result {
-        Ok(()) => {}
+    if let Err(why) = result {
+        let error = RusticError::from_command_error(why);
+        let context = ErrorContext {
+            command: name.to_string(),
+            guild_id: msg.guild_id,
+            channel_id: msg.channel_id,
+            user_id: msg.author.id,
+            args: msg.content.clone(),
+        };
+        let error_id = report(ctx, &error, &context).await;
+        let lang = language(ctx, msg.guild_id, msg.author.id).await;
+        let theme = theme(ctx, msg.guild_id).await;
+
+        if let Err(why) = msg
+            .channel_id
+            .send_message(ctx, |message| {
+                message.set_embed(error.embed(lang, &theme, &error_id))
+            })
+            .await
+        {
+            error!(
+                "Couldn't send the error message for {}: {:?}",
+                error_id, why
+            );
+        }
+    }
+}
+
+/// Use a tag directly by its name when no command matches.
+#[hook]
+async fn unrecognised_command(ctx: &Context, msg: &Message, name: &str) {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+
+    let pool = {
+        let data = ctx.data.read().await;
+        data.get::<PgPoolContainer>().unwrap().clone()
+    };
+
+    match tags::get(&pool, guild_id, &name.to_lowercase()).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return,
         Err(why) => {
-            msg.channel_id
-                .send_message(ctx, |message| {
-                    message.embed(|embed| {
-                        embed.title(":warning: | Unknown Error!");
-                        embed.description(format!("Sorry, An unknown error has occured, it has been reported to my developer.\n\n**Information:**```rust\n{:?}```", why));
-                        embed.color(0xF05B4A);
-                        embed
-                    });
-                    message
-                })
-                .await
-                .unwrap();
-            error!("Command '{}' returned an error {:?}", name, why);
+            error!("Couldn't query database for tags: {}", why);
+            return;
+        }
+    }
+
+    if !check_message(ctx, msg, "tag", command_bucket("tag")).await {
+        return;
+    }
+
+    let args = msg
+        .content
+        .split_once(name)
+        .map_or("", |(_, args)| args.trim());
+
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+    let result = match tag_show_response(
+        ctx,
+        lang,
+        guild_id,
+        msg.author.id,
+        msg.channel_id,
+        name,
+        args,
+    )
+    .await
+    {
+        Ok(response) => response.send(ctx, msg).await,
+        Err(error) => Err(error.into()),
+    };
+
+    after(ctx, msg, "tag", result).await;
+}
+
+/// Every command group, used to look up the usage of a command outside of the framework.
+static GROUPS: &[&CommandGroup] = &[
+    &MISC_GROUP,
+    &FUN_GROUP,
+    &TAGS_GROUP,
+    &REMINDERS_GROUP,
+    &CONFIGURATION_GROUP,
+    &IMAGE_GROUP,
+    &OWNER_GROUP,
+];
+
+/// Sent with every HTTP request the bot makes outside of Discord.
+pub const USER_AGENT: &str = concat!(
+    "Rustic/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/MrArkon/Rustic)"
+);
+
+/// How long the same dispatch error is suppressed for a user.
+const DISPATCH_ERROR_COOLDOWN: Duration = Duration::from_secs(10);
+
+lazy_static! {
+    static ref DISPATCH_ERRORS: DashMap<(UserId, &'static str), Instant> = DashMap::new();
+}
+
+/// Find a top level command by any of its names, along with its group.
+fn find_command(name: &str) -> Option<(&'static CommandGroup, &'static Command)> {
+    GROUPS.iter().find_map(|group| {
+        group
+            .options
+            .commands
+            .iter()
+            .find(|command| {
+                command
+                    .options
+                    .names
+                    .iter()
+                    .any(|n| n.eq_ignore_ascii_case(name))
+            })
+            .map(|command| (*group, *command))
+    })
+}
+
+/// Whether a name is taken by a built-in command, including aliases and the help command.
+fn is_command_name(name: &str) -> bool {
+    BOT_HELP
+        .options
+        .names
+        .iter()
+        .chain(GROUPS.iter().flat_map(|group| {
+            group
+                .options
+                .commands
+                .iter()
+                .flat_map(|command| command.options.names.iter())
+        }))
+        .any(|n| n.eq_ignore_ascii_case(name))
+}
+
+/// A command found in a message.
+struct Invoked {
+    /// The prefix and names used to invoke the command.
+    invocation: String,
+    /// The top level command.
+    parent: &'static Command,
+    /// The subcommand if one was used, the top level command otherwise.
+    command: &'static Command,
+}
+
+/// Find the command (or subcommand) a message invoked, along with the prefix used.
+async fn invoked_command(ctx: &Context, msg: &Message) -> Option<Invoked> {
+    let prefix = guild_prefix(ctx, msg).await;
+    let content = match msg.content.strip_prefix(prefix.as_str()) {
+        Some(content) => content,
+        None => msg.content.split_once('>')?.1,
+    };
+
+    let mut words = content.split_whitespace();
+    let (_, parent) = find_command(words.next()?)?;
+    let mut command = parent;
+    let mut invocation = format!("{}{}", prefix, command.options.names[0]);
+
+    if let Some(name) = words.next() {
+        if let Some(sub_command) = command.options.sub_commands.iter().find(|command| {
+            command
+                .options
+                .names
+                .iter()
+                .any(|n| n.eq_ignore_ascii_case(name))
+        }) {
+            invocation = format!("{} {}", invocation, sub_command.options.names[0]);
+            command = sub_command;
+        }
+    }
+
+    Some(Invoked {
+        invocation,
+        parent,
+        command,
+    })
+}
+
+/// Describe how to use the command a message invoked.
+async fn usage_hint(ctx: &Context, msg: &Message, lang: Lang) -> String {
+    let Invoked {
+        invocation,
+        command,
+        ..
+    } = match invoked_command(ctx, msg).await {
+        Some(invoked) => invoked,
+        None => return String::new(),
+    };
+
+    let usage = match command.options.usage {
+        Some(usage) => format!("{} {}", invocation, usage),
+        None => invocation.clone(),
+    };
+    let mut hint = format!("\n{}", t!(lang, "hint-usage", usage = usage));
+    for example in command.options.examples {
+        let example = format!("{} {}", invocation, example);
+        hint.push_str(&format!(
+            "\n{}",
+            t!(lang, "hint-example", example = example)
+        ));
+    }
+
+    hint
+}
+
+/// Returns true if the user was already told about this error recently.
+fn is_suppressed(user_id: UserId, kind: &'static str) -> bool {
+    let now = Instant::now();
+
+    if let Some(last) = DISPATCH_ERRORS.get(&(user_id, kind)) {
+        if now.duration_since(*last) < DISPATCH_ERROR_COOLDOWN {
+            return true;
         }
     }
+
+    DISPATCH_ERRORS.retain(|_, last| now.duration_since(*last) < DISPATCH_ERROR_COOLDOWN);
+    DISPATCH_ERRORS.insert((user_id, kind), now);
+
+    false
 }
 
 #[hook]
 async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError) {
-    if let DispatchError::Ratelimited(info) = error {
-        if info.is_first_try {
-            let _ = msg
-                .channel_id
-                .say(
-                    &ctx.http,
-                    &format!(
-                        ":hourglass: | **Cooldown:** Try this again in {} seconds.",
-                        info.as_secs()
-                    ),
-                )
-                .await;
+    let lang = language(ctx, msg.guild_id, msg.author.id).await;
+
+    // Checks give the id of the message to show rather than the message itself
+    let (kind, response) = match error {
+        DispatchError::CheckFailed(_, Reason::User(reason)) => (
+            "check_failed",
+            t!(lang, "dispatch-check-failed", reason = t!(lang, &reason)),
+        ),
+        DispatchError::CheckFailed(check, Reason::UserAndLog { user, log }) => {
+            error!("Check '{}' failed: {}", check, log);
+            (
+                "check_failed",
+                t!(lang, "dispatch-check-failed", reason = t!(lang, &user)),
+            )
+        }
+        DispatchError::CheckFailed(check, reason) => {
+            error!("Check '{}' failed: {:?}", check, reason);
+            return;
         }
+        DispatchError::NotEnoughArguments { min, given } => (
+            "not_enough_arguments",
+            t!(
+                lang,
+                "dispatch-not-enough-arguments",
+                min = min,
+                given = given,
+                hint = usage_hint(ctx, msg, lang).await
+            ),
+        ),
+        DispatchError::TooManyArguments { max, given } => (
+            "too_many_arguments",
+            t!(
+                lang,
+                "dispatch-too-many-arguments",
+                max = max,
+                given = given,
+                hint = usage_hint(ctx, msg, lang).await
+            ),
+        ),
+        DispatchError::OnlyForGuilds => ("only_for_guilds", t!(lang, "dispatch-only-for-guilds")),
+        DispatchError::OnlyForDM => ("only_for_dm", t!(lang, "dispatch-only-for-dm")),
+        DispatchError::OnlyForOwners => ("only_for_owners", t!(lang, "dispatch-only-for-owners")),
+        DispatchError::LackingPermissions(permissions) => (
+            "lacking_permissions",
+            t!(
+                lang,
+                "dispatch-lacking-permissions",
+                permissions = permissions.get_permission_names().join(", ")
+            ),
+        ),
+        DispatchError::LackingRole => ("lacking_role", t!(lang, "dispatch-lacking-role")),
+        DispatchError::CommandDisabled(name) => {
+            ("command_disabled", Refusal::Disabled(name).message(lang))
+        }
+        DispatchError::BlockedUser => ("blocked_user", t!(lang, "refusal-blocked")),
+        DispatchError::BlockedGuild => ("blocked_guild", t!(lang, "dispatch-blocked-guild")),
+        DispatchError::BlockedChannel => ("blocked_channel", t!(lang, "dispatch-blocked-channel")),
+        _ => return,
+    };
+
+    if is_suppressed(msg.author.id, kind) {
+        return;
     }
+
+    let _ = msg.channel_id.say(&ctx.http, response).await;
 }
 
-#[hook]
-async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+/// Get the prefix a guild message was sent with, or an empty prefix in DMs.
+async fn guild_prefix(ctx: &Context, msg: &Message) -> String {
     let prefix;
 
-    if let Some(id) = &msg.guild_id {
-        let pool = {
+    if let Some(id) = msg.guild_id {
+        let (pool, prefix_cache) = {
             let data = ctx.data.read().await;
-            data.get::<PgPoolContainer>().unwrap().clone()
+            (
+                data.get::<PgPoolContainer>().unwrap().clone(),
+                data.get::<PrefixCacheContainer>().unwrap().clone(),
+            )
         };
 
-        match query!("SELECT prefix FROM guilds WHERE guild_id=$1", id.0 as i64)
-            .fetch_optional(&pool)
-            .await
-        {
-            Ok(response) => {
-                prefix = if let Some(result) = response {
-                    result.prefix.unwrap_or_else(|| "~".to_string())
-                } else {
-                    "~".to_string()
-                };
-            }
-            Err(why) => {
-                error!("Couldn't query database for prefix: {}", why);
-                prefix = "~".to_string();
-            }
-        };
+        let prefixes = prefix_cache.get(&pool, id).await;
+
+        // The framework only accepts a single dynamic prefix, so hand it the
+        // longest one the message actually starts with.
+        prefix = prefixes
+            .iter()
+            .filter(|prefix| msg.content.starts_with(prefix.as_str()))
+            .max_by_key(|prefix| prefix.len())
+            .unwrap_or(&prefixes[0])
+            .clone();
     } else {
         // No prefix in dms
         prefix = "".to_string();
     };
 
-    Some(prefix)
+    prefix
+}
+
+#[hook]
+async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    Some(guild_prefix(ctx, msg).await)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Initialize settings
-    let settings = settings::init();
+    let settings = match settings::init() {
+        Ok(settings) => settings,
+        Err(why) => {
+            eprintln!("{}", why);
+            std::process::exit(1);
+        }
+    };
 
     // Setup logger
-    let mut logger = formatted_builder();
-    for (path, level) in &settings.logging.filters {
-        logger.filter(Some(path), *level);
-    }
-    logger.init();
+    let (_log_guard, log_filter) = logging::init(&settings.logging);
 
     let http = Http::new_with_token(&settings.bot.token);
 
@@ -219,27 +881,37 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
         Err(why) => panic!("Could not access application info: {:?}", why),
     };
 
-    let framework = StandardFramework::new()
-        .configure(|c| {
-            c.on_mention(Some(bot_id))
-                .dynamic_prefix(dynamic_prefix)
-                .prefix("")
-                .owners(owners)
-                .case_insensitivity(true)
-        })
-        .bucket("basic", |b| b.time_span(5).limit(1))
-        .await
-        .after(after)
-        .on_dispatch_error(dispatch_error)
-        .group(&MISC_GROUP)
-        .group(&FUN_GROUP)
-        .group(&CONFIGURATION_GROUP)
-        .group(&IMAGE_GROUP)
-        .help(&BOT_HELP);
+    let framework = RusticFramework::new(move |blocked_users, blocked_guilds| {
+        StandardFramework::new()
+            .configure(|c| {
+                c.on_mention(Some(bot_id))
+                    .dynamic_prefix(dynamic_prefix)
+                    .prefix("")
+                    .owners(owners.clone())
+                    .blocked_users(blocked_users)
+                    .blocked_guilds(blocked_guilds)
+                    .case_insensitivity(true)
+            })
+            .before(before)
+            .after(after)
+            .on_dispatch_error(dispatch_error)
+            .unrecognised_command(unrecognised_command)
+            .group(&MISC_GROUP)
+            .group(&FUN_GROUP)
+            .group(&TAGS_GROUP)
+            .group(&REMINDERS_GROUP)
+            .group(&CONFIGURATION_GROUP)
+            .group(&IMAGE_GROUP)
+            .group(&OWNER_GROUP)
+            .help(&BOT_HELP)
+    });
 
     let mut client = Client::builder(&settings.bot.token)
-        .event_handler(Handler)
-        .framework(framework)
+        .application_id(settings.bot.application_id)
+        .event_handler(Handler {
+            prewarm_prefix_cache: settings.database.prewarm_prefix_cache,
+        })
+        .framework(framework.clone())
         .await
         .expect("Something went wrong while building the client.");
 
@@ -247,12 +919,7 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
 
     {
         let mut data = client.data.write().await;
-        let reqwest_client = ReqwestClient::builder()
-            .user_agent(format!(
-                "Rustic/{} (https://github.com/MrArkon/Rustic)",
-                env!("CARGO_PKG_VERSION")
-            ))
-            .build()?;
+        let reqwest_client = ReqwestClient::builder().user_agent(USER_AGENT).build()?;
         let pool = PgPoolOptions::new()
             .max_connections(settings.database.max_connections)
             .connect(&settings.database.url)
@@ -264,9 +931,74 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
             .await
             .expect("Couldn't run database migrations");
 
+        framework
+            .sync(&pool)
+            .await
+            .expect("Couldn't load the blocklist");
+
+        // Lift expired blocks, the framework only knows the blocklist it was built with
+        {
+            let framework = framework.clone();
+            let pool = pool.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+
+                    if framework.has_expired_blocks() {
+                        if let Err(why) = framework.sync(&pool).await {
+                            error!("Couldn't refresh the blocklist: {}", why);
+                        }
+                    }
+                }
+            });
+        }
+
+        let language_cache = Arc::new(LanguageCache::default());
+        let scheduler = Arc::new(Scheduler::default());
+        scheduler.start(
+            client.cache_and_http.http.clone(),
+            pool.clone(),
+            language_cache.clone(),
+        );
+
         data.insert::<ShardManagerContainer>(Arc::clone(&client.shard_manager));
         data.insert::<ReqwestContainer>(reqwest_client);
         data.insert::<PgPoolContainer>(pool);
+        data.insert::<PrefixCacheContainer>(Arc::new(PrefixCache::default()));
+        data.insert::<ErrorLogContainer>(settings.bot.error_log_channel.map(ChannelId));
+        data.insert::<RatelimiterContainer>(Arc::new(Ratelimiter::new(
+            settings.ratelimits.clone(),
+        )));
+        data.insert::<LogFilterContainer>(log_filter);
+        data.insert::<PresenceContainer>(Arc::new(Presence::default()));
+        data.insert::<FrameworkContainer>(framework);
+        data.insert::<RuleCacheContainer>(Arc::new(RuleCache::default()));
+        data.insert::<AliasCacheContainer>(Arc::new(AliasCache::default()));
+        data.insert::<LanguageCacheContainer>(language_cache);
+        data.insert::<SchedulerContainer>(scheduler);
+        data.insert::<ThemeCacheContainer>(Arc::new(ThemeCache::default()));
+        data.insert::<TimezoneCacheContainer>(Arc::new(TimezoneCache::default()));
+        data.insert::<ImageWorkersContainer>(Arc::new(ImageWorkers::new(&settings.images)));
+        data.insert::<Settings>(Arc::new(std::sync::Mutex::new(settings)));
+    }
+
+    // Re-read the config file on SIGHUP without reconnecting the shards
+    #[cfg(unix)]
+    {
+        let data = client.data.clone();
+
+        tokio::spawn(async move {
+            let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .expect("Something went wrong while registering SIGHUP handler");
+
+            while hangup.recv().await.is_some() {
+                match reload::reload(&data).await {
+                    Ok(report) => info!("Reloaded settings: {}", report),
+                    Err(why) => error!("Couldn't reload settings, keeping the old ones: {}", why),
+                }
+            }
+        });
     }
 
     let shard_manager = client.shard_manager.clone();
@@ -0,0 +1,420 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The filters of the image commands. A new effect only needs an [`ImageFilter`], an entry
+//! in [`FILTERS`] and a prefix command calling `run_filter`. The decoding, filtering and
+//! encoding run on [`ImageWorkers`] so they never block the shards.
+
+use crate::{error::RusticError, settings::ImageSettings};
+use image::{
+    codecs::gif::{GifDecoder, GifEncoder, Repeat},
+    error::{ImageFormatHint, LimitError, LimitErrorKind, UnsupportedError, UnsupportedErrorKind},
+    imageops::FilterType,
+    io::Reader,
+    AnimationDecoder, DynamicImage, Frame, GenericImageView, ImageDecoder, ImageError, ImageFormat,
+    ImageOutputFormat,
+};
+use std::{
+    io::Cursor,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+
+/// An effect applied to an image.
+pub trait ImageFilter: Send + Sync {
+    /// The name of the command, also used for the file name.
+    fn name(&self) -> &'static str;
+
+    /// The description of the slash command.
+    fn description(&self) -> &'static str;
+
+    fn apply(&self, image: DynamicImage) -> DynamicImage;
+}
+
+pub struct Blur;
+
+impl ImageFilter for Blur {
+    fn name(&self) -> &'static str {
+        "blur"
+    }
+
+    fn description(&self) -> &'static str {
+        "Blur an image, a member's avatar or the last image in the channel."
+    }
+
+    fn apply(&self, image: DynamicImage) -> DynamicImage {
+        image.blur(5.0)
+    }
+}
+
+pub struct Grayscale;
+
+impl ImageFilter for Grayscale {
+    fn name(&self) -> &'static str {
+        "grayscale"
+    }
+
+    fn description(&self) -> &'static str {
+        "Make an image, a member's avatar or the last image in the channel grayscale."
+    }
+
+    fn apply(&self, image: DynamicImage) -> DynamicImage {
+        image.grayscale()
+    }
+}
+
+/// Every filter, each one is also a prefix and a slash command.
+pub static FILTERS: &[&dyn ImageFilter] = &[&Blur, &Grayscale];
+
+pub fn find_filter(name: &str) -> Option<&'static dyn ImageFilter> {
+    FILTERS.iter().find(|filter| filter.name() == name).copied()
+}
+
+/// The formats the image commands read, recognized by their content.
+pub const INPUT_FORMATS: &[ImageFormat] = &[
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::WebP,
+    ImageFormat::Gif,
+    ImageFormat::Bmp,
+];
+
+/// Largest file Discord accepts from the bot, in bytes.
+pub const MAX_UPLOAD_SIZE: usize = 8 * 1024 * 1024;
+/// Pixels of a single image or frame, checked before decoding since a small file can claim
+/// to be huge.
+pub const MAX_PIXELS: u64 = 32 * 1024 * 1024;
+/// Pixels of all the frames of a GIF together, so a long animation can't use up the memory.
+const MAX_GIF_PIXELS: u64 = 64 * 1024 * 1024;
+/// From 1 to 30, slower gives GIFs better colours.
+const GIF_SPEED: i32 = 10;
+
+#[derive(Clone, Copy)]
+pub struct EncodeOptions {
+    pub jpeg_quality: u8,
+    pub max_frames: usize,
+}
+
+/// An encoded filtered image.
+pub struct Output {
+    pub data: Vec<u8>,
+    pub extension: &'static str,
+    /// An animated GIF which was too long or too large, so only its first frame was used.
+    pub flattened: bool,
+}
+
+/// Decode an image, apply the filter and encode the result. Photos stay JPEGs, animated
+/// GIFs stay animated and everything else becomes a PNG so transparency is kept.
+pub fn process(
+    filter: &dyn ImageFilter,
+    bytes: &[u8],
+    options: EncodeOptions,
+) -> Result<Output, ImageError> {
+    let format = image::guess_format(bytes)?;
+
+    if !INPUT_FORMATS.contains(&format) {
+        return Err(ImageError::Unsupported(
+            UnsupportedError::from_format_and_kind(
+                ImageFormatHint::Exact(format),
+                UnsupportedErrorKind::Format(ImageFormatHint::Exact(format)),
+            ),
+        ));
+    }
+
+    let (width, height) = Reader::with_format(Cursor::new(bytes), format).into_dimensions()?;
+    if u64::from(width) * u64::from(height) > MAX_PIXELS {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::DimensionError,
+        )));
+    }
+
+    if format == ImageFormat::Gif {
+        return process_gif(filter, bytes, options);
+    }
+
+    let image = filter.apply(image::load_from_memory_with_format(bytes, format)?);
+    encode_static(image, format, options.jpeg_quality)
+}
+
+/// Encode a still image, shrinking it until the file fits in an upload.
+fn encode_static(
+    mut image: DynamicImage,
+    format: ImageFormat,
+    jpeg_quality: u8,
+) -> Result<Output, ImageError> {
+    loop {
+        let output = encode_once(&image, format, jpeg_quality)?;
+        if output.data.len() <= MAX_UPLOAD_SIZE {
+            return Ok(output);
+        }
+
+        // The size doesn't shrink exactly with the area, so leave a margin
+        let scale = (MAX_UPLOAD_SIZE as f64 / output.data.len() as f64).sqrt() * 0.9;
+        let width = ((f64::from(image.width()) * scale) as u32).max(1);
+        let height = ((f64::from(image.height()) * scale) as u32).max(1);
+        image = image.resize(width, height, FilterType::Triangle);
+    }
+}
+
+fn encode_once(
+    image: &DynamicImage,
+    format: ImageFormat,
+    jpeg_quality: u8,
+) -> Result<Output, ImageError> {
+    let mut data = Vec::new();
+
+    if format == ImageFormat::Jpeg && !image.color().has_alpha() {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_to(&mut data, ImageOutputFormat::Jpeg(jpeg_quality))?;
+
+        return Ok(Output {
+            data,
+            extension: "jpg",
+            flattened: false,
+        });
+    }
+
+    image.write_to(&mut data, ImageOutputFormat::Png)?;
+
+    Ok(Output {
+        data,
+        extension: "png",
+        flattened: false,
+    })
+}
+
+/// Filter every frame of a GIF. The decoder already draws each frame over the previous
+/// ones the way their disposal says, so the frames are whole images which keep their delays.
+fn process_gif(
+    filter: &dyn ImageFilter,
+    bytes: &[u8],
+    options: EncodeOptions,
+) -> Result<Output, ImageError> {
+    let decoder = GifDecoder::new(Cursor::new(bytes))?;
+    let (width, height) = decoder.dimensions();
+    let frame_pixels = (u64::from(width) * u64::from(height)).max(1);
+    // `process` already refused frames over `MAX_PIXELS`, so at least one frame fits
+    let max_frames = options
+        .max_frames
+        .min((MAX_GIF_PIXELS / frame_pixels) as usize)
+        .max(1);
+
+    let mut frames = Vec::new();
+    let mut too_long = false;
+    for frame in decoder.into_frames() {
+        if frames.len() == max_frames {
+            too_long = true;
+            break;
+        }
+        frames.push(frame?);
+    }
+
+    // The decoder only reads the first frame of a GIF as a still image
+    let first_frame = |flattened| -> Result<Output, ImageError> {
+        let image = filter.apply(image::load_from_memory_with_format(
+            bytes,
+            ImageFormat::Gif,
+        )?);
+        let mut output = encode_static(image, ImageFormat::Gif, options.jpeg_quality)?;
+        output.flattened = flattened;
+        Ok(output)
+    };
+
+    if too_long {
+        return first_frame(true);
+    }
+    if frames.len() < 2 {
+        return first_frame(false);
+    }
+
+    let mut data = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut data, GIF_SPEED);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(frames.into_iter().map(|frame| {
+            let (left, top, delay) = (frame.left(), frame.top(), frame.delay());
+            let image = filter.apply(DynamicImage::ImageRgba8(frame.into_buffer()));
+            Frame::from_parts(image.into_rgba8(), left, top, delay)
+        }))?;
+    }
+
+    if data.len() > MAX_UPLOAD_SIZE {
+        return first_frame(true);
+    }
+
+    Ok(Output {
+        data,
+        extension: "gif",
+        flattened: false,
+    })
+}
+
+/// Runs image jobs on the blocking thread pool, a few at a time.
+pub struct ImageWorkers {
+    /// How the jobs encode the images they filtered.
+    pub options: EncodeOptions,
+    jobs: Arc<Semaphore>,
+    /// Jobs running or waiting for their turn.
+    pending: AtomicUsize,
+    max_pending: usize,
+    timeout: Duration,
+}
+
+/// Counts a job as pending until the caller stops waiting for it.
+struct Pending<'a>(&'a AtomicUsize);
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ImageWorkers {
+    pub fn new(settings: &ImageSettings) -> Self {
+        ImageWorkers {
+            options: EncodeOptions {
+                jpeg_quality: settings.jpeg_quality,
+                max_frames: settings.max_frames,
+            },
+            jobs: Arc::new(Semaphore::new(settings.max_jobs)),
+            pending: AtomicUsize::new(0),
+            max_pending: settings.max_jobs + settings.max_queue,
+            timeout: Duration::from_secs(settings.timeout),
+        }
+    }
+
+    /// Run a job once one of the workers is free, or refuse it if too many are waiting.
+    pub async fn run<T, F>(&self, job: F) -> Result<T, RusticError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, ImageError> + Send + 'static,
+    {
+        if self.pending.fetch_add(1, Ordering::SeqCst) >= self.max_pending {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err(RusticError::Busy);
+        }
+        let _pending = Pending(&self.pending);
+
+        let jobs = self.jobs.clone();
+        let result = tokio::time::timeout(self.timeout, async move {
+            let permit = jobs.acquire_owned().await.map_err(RusticError::internal)?;
+
+            tokio::task::spawn_blocking(move || {
+                // A job that timed out still holds its permit until it's actually done
+                let _permit = permit;
+                job()
+            })
+            .await
+            .map_err(RusticError::internal)
+        })
+        .await;
+
+        match result {
+            Ok(result) => Ok(result??),
+            Err(_) => Err(RusticError::TimedOut),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Delay, Rgba, RgbaImage};
+
+    const OPTIONS: EncodeOptions = EncodeOptions {
+        jpeg_quality: 85,
+        max_frames: 10,
+    };
+
+    fn animation(frames: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut data);
+            for i in 0..frames {
+                let image = RgbaImage::from_pixel(8, 8, Rgba([i * 20, 50, 0, 255]));
+                encoder
+                    .encode_frame(Frame::from_parts(
+                        image,
+                        0,
+                        0,
+                        Delay::from_numer_denom_ms(70, 1),
+                    ))
+                    .unwrap();
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn keeps_animations_animated() {
+        let output = process(&Grayscale, &animation(3), OPTIONS).unwrap();
+        assert_eq!(output.extension, "gif");
+        assert!(!output.flattened);
+
+        let frames = GifDecoder::new(Cursor::new(output.data))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].delay().numer_denom_ms(), (70, 1));
+        let Rgba([red, green, blue, _]) = *frames[1].buffer().get_pixel(0, 0);
+        assert!(red == green && green == blue);
+    }
+
+    #[test]
+    fn flattens_long_animations() {
+        let output = process(&Grayscale, &animation(12), OPTIONS).unwrap();
+        assert_eq!(output.extension, "png");
+        assert!(output.flattened);
+    }
+
+    #[test]
+    fn shrinks_images_too_large_to_upload() {
+        // Noise doesn't compress, so this is well over the upload limit as a PNG
+        let mut seed = 1u32;
+        let image = RgbaImage::from_fn(1600, 1600, |_, _| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            Rgba(seed.to_le_bytes())
+        });
+        let output = encode_static(
+            DynamicImage::ImageRgba8(image),
+            ImageFormat::Png,
+            OPTIONS.jpeg_quality,
+        )
+        .unwrap();
+
+        assert!(output.data.len() <= MAX_UPLOAD_SIZE);
+        let shrunk = image::load_from_memory(&output.data).unwrap();
+        assert!(shrunk.width() < 1600 && shrunk.width() > 400);
+    }
+
+    #[test]
+    fn refuses_huge_images_before_decoding() {
+        // A GIF header claiming 60000x60000 pixels with a 1x1 frame
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&[0x60, 0xea, 0x60, 0xea, 0, 0, 0]);
+        gif.extend_from_slice(&[0x2c, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x4c, 0x01, 0, 0x3b]);
+
+        assert!(matches!(
+            process(&Grayscale, &gif, OPTIONS),
+            Err(ImageError::Limits(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    cache::DEFAULT_PREFIX,
+    settings::{ActivityKind, ActivitySettings, PresenceStatus, Settings, MAX_ACTIVITY_LENGTH},
+};
+use dashmap::DashMap;
+use serenity::{
+    model::prelude::{Activity, OnlineStatus},
+    prelude::Context,
+};
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::{sync::Notify, task::JoinHandle};
+
+/// An activity shown instead of the configured ones until it expires or is cleared.
+#[derive(Clone)]
+pub struct Override {
+    pub activity: ActivitySettings,
+    pub until: Option<Instant>,
+}
+
+/// Rotates the configured activities on every shard.
+#[derive(Default)]
+pub struct Presence {
+    temporary: RwLock<Option<Override>>,
+    tasks: DashMap<u64, JoinHandle<()>>,
+    refresh: Notify,
+}
+
+fn activity(kind: ActivityKind, text: &str) -> Activity {
+    match kind {
+        ActivityKind::Playing => Activity::playing(text),
+        ActivityKind::Listening => Activity::listening(text),
+        ActivityKind::Watching => Activity::watching(text),
+        ActivityKind::Competing => Activity::competing(text),
+    }
+}
+
+fn status(status: PresenceStatus) -> OnlineStatus {
+    match status {
+        PresenceStatus::Online => OnlineStatus::Online,
+        PresenceStatus::Idle => OnlineStatus::Idle,
+        PresenceStatus::Dnd => OnlineStatus::DoNotDisturb,
+        PresenceStatus::Invisible => OnlineStatus::Invisible,
+    }
+}
+
+/// Fill in the placeholders of an activity text.
+async fn render(ctx: &Context, text: &str) -> String {
+    let mut text = text
+        .replace("{shard}", &(ctx.shard_id + 1).to_string())
+        .replace("{prefix}", DEFAULT_PREFIX);
+
+    if text.contains("{guilds}") {
+        text = text.replace("{guilds}", &ctx.cache.guilds().await.len().to_string());
+    }
+    if text.contains("{users}") {
+        text = text.replace("{users}", &ctx.cache.user_count().await.to_string());
+    }
+
+    text.chars().take(MAX_ACTIVITY_LENGTH).collect()
+}
+
+impl Presence {
+    /// Show `activity` on every shard, until `duration` has passed if one is given.
+    pub fn set_override(&self, activity: ActivitySettings, duration: Option<Duration>) {
+        *self.temporary.write().unwrap() = Some(Override {
+            activity,
+            until: duration.map(|duration| Instant::now() + duration),
+        });
+        self.refresh();
+    }
+
+    pub fn clear_override(&self) {
+        *self.temporary.write().unwrap() = None;
+        self.refresh();
+    }
+
+    /// The override currently shown, expired overrides are removed.
+    pub fn current_override(&self) -> Option<Override> {
+        let mut temporary = self.temporary.write().unwrap();
+
+        if let Some(until) = temporary.as_ref().and_then(|temporary| temporary.until) {
+            if Instant::now() >= until {
+                *temporary = None;
+            }
+        }
+
+        temporary.clone()
+    }
+
+    /// Update the presence of every shard right away instead of at the next rotation.
+    pub fn refresh(&self) {
+        self.refresh.notify_waiters();
+    }
+
+    /// Start rotating the activities of the shard `ctx` belongs to, replacing the
+    /// task of a previous session of that shard.
+    pub fn start(self: &Arc<Self>, ctx: Context) {
+        let presence = Arc::clone(self);
+        let shard_id = ctx.shard_id;
+
+        let task = tokio::spawn(async move {
+            let mut index = 0;
+
+            loop {
+                let settings = {
+                    let data = ctx.data.read().await;
+                    let settings = data.get::<Settings>().unwrap().lock().unwrap();
+                    settings.presence.clone()
+                };
+
+                let current = match presence.current_override() {
+                    Some(temporary) => Some(temporary.activity),
+                    None => settings
+                        .activities
+                        .get(index % settings.activities.len().max(1))
+                        .cloned(),
+                };
+                index += 1;
+
+                if let Some(current) = current {
+                    let text = render(&ctx, &current.text).await;
+                    ctx.set_presence(Some(activity(current.kind, &text)), status(settings.status))
+                        .await;
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(settings.interval)) => {}
+                    _ = presence.refresh.notified() => {}
+                }
+            }
+        });
+
+        if let Some(previous) = self.tasks.insert(shard_id, task) {
+            previous.abort();
+        }
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ratelimit buckets, kept outside of the framework so they can be reconfigured
+//! while the bot is running.
+
+use crate::{
+    settings::{BucketScope, BucketSettings},
+    PgPoolContainer,
+};
+use dashmap::DashMap;
+use serenity::{
+    model::prelude::{ChannelId, GuildId, RoleId, UserId},
+    prelude::Context,
+};
+use sqlx::query;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+use tracing::error;
+
+pub enum Ratelimit {
+    Allowed,
+    /// The invocation was queued and may run after waiting.
+    Wait(Duration),
+    /// The invocation was rejected, it can be retried after the duration.
+    Limited {
+        wait: Duration,
+        first_try: bool,
+    },
+}
+
+#[derive(Default)]
+struct BucketState {
+    uses: VecDeque<Instant>,
+    waiting: u32,
+    limited: bool,
+}
+
+/// Where a command was invoked, used to pick the bucket key for its scope.
+pub struct Invocation {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    pub guild_id: Option<GuildId>,
+    pub category_id: Option<ChannelId>,
+    pub roles: Vec<RoleId>,
+}
+
+impl Invocation {
+    fn key(&self, scope: BucketScope) -> u64 {
+        match scope {
+            BucketScope::User => self.user_id.0,
+            BucketScope::Channel => self.channel_id.0,
+            BucketScope::Guild => self.guild_id.map_or(self.channel_id.0, |id| id.0),
+            BucketScope::Category => self.category_id.unwrap_or(self.channel_id).0,
+            BucketScope::Global => 0,
+        }
+    }
+}
+
+pub struct Ratelimiter {
+    buckets: RwLock<HashMap<String, BucketSettings>>,
+    states: DashMap<(String, u64), BucketState>,
+    /// Per-guild cache of the `premium` flag, like the prefix cache.
+    premium: DashMap<GuildId, bool>,
+}
+
+impl Ratelimiter {
+    pub fn new(buckets: HashMap<String, BucketSettings>) -> Self {
+        Ratelimiter {
+            buckets: RwLock::new(buckets),
+            states: DashMap::new(),
+            premium: DashMap::new(),
+        }
+    }
+
+    /// Replace the bucket definitions, resetting the usage of every bucket.
+    pub fn configure(&self, buckets: HashMap<String, BucketSettings>) {
+        *self.buckets.write().unwrap() = buckets;
+        self.states.clear();
+    }
+
+    /// Forget the cached premium flags. Premium is set in the database by hand, so this
+    /// runs on every reload.
+    pub fn clear_premium(&self) {
+        self.premium.clear();
+    }
+
+    /// Record an invocation of a command using `bucket`.
+    pub async fn hit(&self, ctx: &Context, bucket: &str, invocation: &Invocation) -> Ratelimit {
+        let settings = match self.buckets.read().unwrap().get(bucket) {
+            Some(settings) => settings.clone(),
+            None => return Ratelimit::Allowed,
+        };
+
+        if settings.premium_exempt && self.is_premium(ctx, invocation.guild_id).await {
+            return Ratelimit::Allowed;
+        }
+
+        self.record(
+            bucket,
+            &settings,
+            invocation.key(settings.scope),
+            Instant::now(),
+        )
+    }
+
+    /// Count a use of `bucket` by `key` at `now`, unless its window or delay refuses it.
+    fn record(&self, bucket: &str, settings: &BucketSettings, key: u64, now: Instant) -> Ratelimit {
+        let delay = Duration::from_secs(settings.delay);
+        let time_span = Duration::from_secs(settings.time_span);
+        let limit = settings.limit as usize;
+
+        let mut state = self.states.entry((bucket.to_string(), key)).or_default();
+
+        while let Some(first) = state.uses.front() {
+            if now.saturating_duration_since(*first) >= time_span.max(delay) {
+                state.uses.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let mut wait = Duration::ZERO;
+
+        if let Some(last) = state.uses.back() {
+            wait = wait.max((*last + delay).saturating_duration_since(now));
+        }
+
+        if limit > 0 && !time_span.is_zero() && state.uses.len() >= limit {
+            let oldest = state.uses[state.uses.len() - limit];
+            wait = wait.max((oldest + time_span).saturating_duration_since(now));
+        }
+
+        if wait.is_zero() {
+            state.uses.push_back(now);
+            state.limited = false;
+            Ratelimit::Allowed
+        } else if state.waiting < settings.await_ratelimits {
+            state.uses.push_back(now + wait);
+            state.waiting += 1;
+            state.limited = false;
+            Ratelimit::Wait(wait)
+        } else {
+            let first_try = !state.limited;
+            state.limited = true;
+            Ratelimit::Limited { wait, first_try }
+        }
+    }
+
+    /// Release the queue slot taken by an invocation which had to wait.
+    pub fn finish_waiting(&self, bucket: &str, invocation: &Invocation) {
+        let scope = match self.buckets.read().unwrap().get(bucket) {
+            Some(settings) => settings.scope,
+            None => return,
+        };
+
+        if let Some(mut state) = self
+            .states
+            .get_mut(&(bucket.to_string(), invocation.key(scope)))
+        {
+            state.waiting = state.waiting.saturating_sub(1);
+        }
+    }
+
+    /// Premium guilds can bypass the buckets marked with `premium_exempt`.
+    async fn is_premium(&self, ctx: &Context, guild_id: Option<GuildId>) -> bool {
+        let guild_id = match guild_id {
+            Some(id) => id,
+            None => return false,
+        };
+
+        if let Some(premium) = self.premium.get(&guild_id) {
+            return *premium;
+        }
+
+        let pool = {
+            let data = ctx.data.read().await;
+            data.get::<PgPoolContainer>().unwrap().clone()
+        };
+
+        match query!(
+            "SELECT premium FROM guilds WHERE guild_id=$1",
+            guild_id.0 as i64
+        )
+        .fetch_optional(&pool)
+        .await
+        {
+            Ok(response) => {
+                let premium = response.is_some_and(|result| result.premium);
+                self.premium.insert(guild_id, premium);
+                premium
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next command retries the database
+                error!("Couldn't query database for premium status: {}", why);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: u64 = 1;
+
+    fn bucket(limit: u32, time_span: u64, delay: u64, await_ratelimits: u32) -> BucketSettings {
+        BucketSettings {
+            delay,
+            time_span,
+            limit,
+            scope: BucketScope::User,
+            await_ratelimits,
+            premium_exempt: false,
+        }
+    }
+
+    fn ratelimiter(settings: &BucketSettings) -> Ratelimiter {
+        Ratelimiter::new(HashMap::from([("test".to_string(), settings.clone())]))
+    }
+
+    fn at(start: Instant, seconds: u64) -> Instant {
+        start + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn allows_uses_within_the_window() {
+        let settings = bucket(2, 10, 0, 0);
+        let ratelimiter = ratelimiter(&settings);
+        let start = Instant::now();
+        let hit = |seconds| ratelimiter.record("test", &settings, USER, at(start, seconds));
+
+        assert!(matches!(hit(0), Ratelimit::Allowed));
+        assert!(matches!(hit(1), Ratelimit::Allowed));
+        assert!(matches!(
+            hit(2),
+            Ratelimit::Limited { wait, first_try: true } if wait == Duration::from_secs(8)
+        ));
+        // Only the first refusal is reported
+        assert!(matches!(
+            hit(3),
+            Ratelimit::Limited {
+                first_try: false,
+                ..
+            }
+        ));
+        // The first use left the window
+        assert!(matches!(hit(10), Ratelimit::Allowed));
+        assert!(matches!(hit(10), Ratelimit::Limited { .. }));
+    }
+
+    #[test]
+    fn keeps_users_apart() {
+        let settings = bucket(1, 10, 0, 0);
+        let ratelimiter = ratelimiter(&settings);
+        let now = Instant::now();
+
+        assert!(matches!(
+            ratelimiter.record("test", &settings, USER, now),
+            Ratelimit::Allowed
+        ));
+        assert!(matches!(
+            ratelimiter.record("test", &settings, USER + 1, now),
+            Ratelimit::Allowed
+        ));
+    }
+
+    #[test]
+    fn enforces_the_delay_between_uses() {
+        let settings = bucket(0, 0, 5, 0);
+        let ratelimiter = ratelimiter(&settings);
+        let start = Instant::now();
+        let hit = |seconds| ratelimiter.record("test", &settings, USER, at(start, seconds));
+
+        assert!(matches!(hit(0), Ratelimit::Allowed));
+        assert!(matches!(
+            hit(2),
+            Ratelimit::Limited { wait, .. } if wait == Duration::from_secs(3)
+        ));
+        assert!(matches!(hit(5), Ratelimit::Allowed));
+    }
+
+    #[test]
+    fn queues_uses_up_to_the_await_limit() {
+        let settings = bucket(1, 10, 0, 1);
+        let ratelimiter = ratelimiter(&settings);
+        let start = Instant::now();
+        let hit = |seconds| ratelimiter.record("test", &settings, USER, at(start, seconds));
+
+        assert!(matches!(hit(0), Ratelimit::Allowed));
+        assert!(matches!(
+            hit(1),
+            Ratelimit::Wait(wait) if wait == Duration::from_secs(9)
+        ));
+        // The queue is full
+        assert!(matches!(hit(2), Ratelimit::Limited { .. }));
+    }
+
+    #[test]
+    fn resets_on_reconfigure() {
+        let settings = bucket(1, 60, 0, 0);
+        let ratelimiter = ratelimiter(&settings);
+        let now = Instant::now();
+
+        assert!(matches!(
+            ratelimiter.record("test", &settings, USER, now),
+            Ratelimit::Allowed
+        ));
+        assert!(matches!(
+            ratelimiter.record("test", &settings, USER, now),
+            Ratelimit::Limited { .. }
+        ));
+
+        ratelimiter.configure(HashMap::from([("test".to_string(), settings.clone())]));
+        assert!(matches!(
+            ratelimiter.record("test", &settings, USER, now),
+            Ratelimit::Allowed
+        ));
+    }
+
+    #[test]
+    fn picks_the_key_for_the_scope() {
+        let invocation = Invocation {
+            user_id: UserId(1),
+            channel_id: ChannelId(2),
+            guild_id: Some(GuildId(3)),
+            category_id: None,
+            roles: Vec::new(),
+        };
+
+        assert_eq!(invocation.key(BucketScope::User), 1);
+        assert_eq!(invocation.key(BucketScope::Channel), 2);
+        assert_eq!(invocation.key(BucketScope::Guild), 3);
+        assert_eq!(invocation.key(BucketScope::Category), 2);
+        assert_eq!(invocation.key(BucketScope::Global), 0);
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+    logging,
+    settings::{self, Settings, SettingsError},
+    ErrorLogContainer, LogFilterContainer, PresenceContainer, RatelimiterContainer,
+};
+use serenity::{
+    model::prelude::ChannelId,
+    prelude::{RwLock, TypeMap},
+};
+use std::fmt;
+use tracing::error;
+
+/// The settings which changed in a reload.
+#[derive(Default)]
+pub struct ReloadReport {
+    pub applied: Vec<&'static str>,
+    /// Changed settings which are only read on startup, they keep their old value until then.
+    pub restart_required: Vec<&'static str>,
+}
+
+impl fmt::Display for ReloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.applied.is_empty() {
+            write!(f, "No changes applied")?;
+        } else {
+            write!(f, "Applied {}", self.applied.join(", "))?;
+        }
+
+        if !self.restart_required.is_empty() {
+            write!(
+                f,
+                ", restart required for {}",
+                self.restart_required.join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keep the running value of a setting which can't be changed without a restart.
+fn keep<T: PartialEq + Clone>(
+    key: &'static str,
+    new: &mut T,
+    current: &T,
+    report: &mut ReloadReport,
+) {
+    if new != current {
+        report.restart_required.push(key);
+        *new = current.clone();
+    }
+}
+
+/// Re-read the settings and apply everything which can change while the shards are connected.
+pub async fn reload(data: &RwLock<TypeMap>) -> Result<ReloadReport, SettingsError> {
+    let mut new = settings::init()?;
+    let mut report = ReloadReport::default();
+
+    let (shared, ratelimiter, log_filter, presence) = {
+        let data = data.read().await;
+        (
+            data.get::<Settings>().unwrap().clone(),
+            data.get::<RatelimiterContainer>().unwrap().clone(),
+            data.get::<LogFilterContainer>().unwrap().clone(),
+            data.get::<PresenceContainer>().unwrap().clone(),
+        )
+    };
+
+    let old = {
+        let mut current = shared.lock().unwrap();
+
+        keep(
+            "bot.token",
+            &mut new.bot.token,
+            &current.bot.token,
+            &mut report,
+        );
+        keep(
+            "bot.application_id",
+            &mut new.bot.application_id,
+            &current.bot.application_id,
+            &mut report,
+        );
+        keep(
+            "database",
+            &mut new.database,
+            &current.database,
+            &mut report,
+        );
+        keep("images", &mut new.images, &current.images, &mut report);
+        keep(
+            "logging.format",
+            &mut new.logging.format,
+            &current.logging.format,
+            &mut report,
+        );
+        keep(
+            "logging.file",
+            &mut new.logging.file,
+            &current.logging.file,
+            &mut report,
+        );
+        keep(
+            "logging.rotation",
+            &mut new.logging.rotation,
+            &current.logging.rotation,
+            &mut report,
+        );
+
+        std::mem::replace(&mut *current, new.clone())
+    };
+
+    if new.logging.level != old.logging.level || new.logging.filters != old.logging.filters {
+        match log_filter.reload(logging::filter(&new.logging)) {
+            Ok(()) => report.applied.push("logging"),
+            Err(why) => error!("Couldn't apply the new log filters: {}", why),
+        }
+    }
+
+    if new.ratelimits != old.ratelimits {
+        ratelimiter.configure(new.ratelimits.clone());
+        report.applied.push("ratelimits");
+    }
+    ratelimiter.clear_premium();
+
+    if new.features != old.features {
+        report.applied.push("features");
+    }
+
+    if new.bot.error_log_channel != old.bot.error_log_channel {
+        data.write()
+            .await
+            .insert::<ErrorLogContainer>(new.bot.error_log_channel.map(ChannelId));
+        report.applied.push("bot.error_log_channel");
+    }
+
+    if new.presence != old.presence {
+        presence.refresh();
+        report.applied.push("presence");
+    }
+
+    Ok(report)
+}
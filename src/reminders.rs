@@ -0,0 +1,323 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reminders stored in the database and the task delivering them. Everything lives in the
+//! `reminders` table, so pending reminders carry over restarts.
+
+use crate::i18n::{t, LanguageCache};
+use chrono::{DateTime, Duration, Utc};
+use serenity::{
+    http::Http,
+    model::id::{ChannelId, GuildId, UserId},
+};
+use sqlx::{query, PgPool};
+use std::{sync::Arc, time::Duration as StdDuration};
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+/// The longest the scheduler sleeps before looking at the database again.
+const MAX_SLEEP: StdDuration = StdDuration::from_secs(60);
+/// How long to wait before retrying reminders the database couldn't mark as sent.
+const RETRY_DELAY: StdDuration = StdDuration::from_secs(30);
+/// Reminders delivered this much after they were due mention it.
+const LATE_AFTER: i64 = 120;
+
+pub struct Reminder {
+    pub id: i64,
+    pub user_id: UserId,
+    pub guild_id: Option<GuildId>,
+    /// `None` delivers the reminder in a direct message.
+    pub channel_id: Option<ChannelId>,
+    pub content: String,
+    pub due_at: DateTime<Utc>,
+    /// How often a recurring reminder repeats.
+    pub interval: Option<Duration>,
+}
+
+pub async fn create(pool: &PgPool, reminder: &Reminder) -> Result<i64, sqlx::Error> {
+    let row = query!(
+        "INSERT INTO reminders (user_id, guild_id, channel_id, content, due_at, interval_seconds) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        reminder.user_id.0 as i64,
+        reminder.guild_id.map(|id| id.0 as i64),
+        reminder.channel_id.map(|id| id.0 as i64),
+        reminder.content,
+        reminder.due_at,
+        reminder.interval.map(|interval| interval.num_seconds())
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.id)
+}
+
+/// The pending reminders of a user, soonest first.
+pub async fn list(pool: &PgPool, user_id: UserId) -> Result<Vec<Reminder>, sqlx::Error> {
+    let rows = query!(
+        "SELECT id, user_id, guild_id, channel_id, content, due_at, interval_seconds FROM reminders WHERE user_id=$1 ORDER BY due_at",
+        user_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Reminder {
+            id: row.id,
+            user_id: UserId(row.user_id as u64),
+            guild_id: row.guild_id.map(|id| GuildId(id as u64)),
+            channel_id: row.channel_id.map(|id| ChannelId(id as u64)),
+            content: row.content,
+            due_at: row.due_at,
+            interval: row.interval_seconds.map(Duration::seconds),
+        })
+        .collect())
+}
+
+pub async fn count(pool: &PgPool, user_id: UserId) -> Result<i64, sqlx::Error> {
+    let row = query!(
+        r#"SELECT COUNT(*) AS "count!" FROM reminders WHERE user_id=$1"#,
+        user_id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.count)
+}
+
+/// Delete a reminder of a user, returning false if they don't have it.
+pub async fn delete(pool: &PgPool, user_id: UserId, id: i64) -> Result<bool, sqlx::Error> {
+    let result = query!(
+        "DELETE FROM reminders WHERE id=$1 AND user_id=$2",
+        id,
+        user_id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn due(pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<Reminder>, sqlx::Error> {
+    let rows = query!(
+        "SELECT id, user_id, guild_id, channel_id, content, due_at, interval_seconds FROM reminders WHERE due_at <= $1 ORDER BY due_at LIMIT 100",
+        now
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Reminder {
+            id: row.id,
+            user_id: UserId(row.user_id as u64),
+            guild_id: row.guild_id.map(|id| GuildId(id as u64)),
+            channel_id: row.channel_id.map(|id| ChannelId(id as u64)),
+            content: row.content,
+            due_at: row.due_at,
+            interval: row.interval_seconds.map(Duration::seconds),
+        })
+        .collect())
+}
+
+async fn next_due(pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let row = query!("SELECT MIN(due_at) AS next FROM reminders")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.next)
+}
+
+/// When a recurring reminder is due next, skipping the times missed while the bot was
+/// offline instead of sending them all at once.
+fn next_time(reminder: &Reminder, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let interval = reminder.interval?.num_seconds();
+
+    if interval <= 0 {
+        return None;
+    }
+
+    let missed = (now - reminder.due_at).num_seconds().max(0) / interval;
+    Some(reminder.due_at + Duration::seconds(interval * (missed + 1)))
+}
+
+/// Move a recurring reminder to its next time, or delete a reminder that doesn't repeat.
+async fn finish(pool: &PgPool, reminder: &Reminder, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    let next = match next_time(reminder, now) {
+        Some(next) => next,
+        None => {
+            query!("DELETE FROM reminders WHERE id=$1", reminder.id)
+                .execute(pool)
+                .await?;
+            return Ok(());
+        }
+    };
+
+    query!(
+        "UPDATE reminders SET due_at = $2 WHERE id=$1",
+        reminder.id,
+        next
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Delivers reminders when they are due.
+#[derive(Default)]
+pub struct Scheduler {
+    wake: Notify,
+}
+
+impl Scheduler {
+    /// Look at the database again, for when a reminder was added that may be due sooner.
+    pub fn refresh(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Start delivering reminders, including the ones left from before a restart.
+    pub fn start(
+        self: &Arc<Self>,
+        http: Arc<Http>,
+        pool: PgPool,
+        language_cache: Arc<LanguageCache>,
+    ) {
+        let scheduler = Arc::clone(self);
+
+        tokio::spawn(async move {
+            match query!(r#"SELECT COUNT(*) AS "count!" FROM reminders"#)
+                .fetch_one(&pool)
+                .await
+            {
+                Ok(row) => info!("Loaded {} pending reminders.", row.count),
+                Err(why) => error!("Couldn't count the pending reminders: {}", why),
+            }
+
+            loop {
+                let now = Utc::now();
+                let mut failed = false;
+
+                match due(&pool, now).await {
+                    Ok(reminders) => {
+                        for reminder in reminders {
+                            // Finish first, a reminder that stays due would be sent every tick
+                            if let Err(why) = finish(&pool, &reminder, now).await {
+                                error!("Couldn't finish reminder {}: {}", reminder.id, why);
+                                failed = true;
+                                continue;
+                            }
+
+                            deliver(&http, &pool, &language_cache, &reminder, now).await;
+                        }
+                    }
+                    Err(why) => error!("Couldn't query database for due reminders: {}", why),
+                }
+
+                let wait = match next_due(&pool).await {
+                    Ok(Some(next)) => (next - Utc::now())
+                        .to_std()
+                        .unwrap_or_default()
+                        .min(MAX_SLEEP),
+                    Ok(None) => MAX_SLEEP,
+                    Err(why) => {
+                        error!("Couldn't query database for the next reminder: {}", why);
+                        MAX_SLEEP
+                    }
+                };
+                // The reminders that couldn't be finished are still due, don't retry right away
+                let wait = if failed { wait.max(RETRY_DELAY) } else { wait };
+
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = scheduler.wake.notified() => {}
+                }
+            }
+        });
+    }
+}
+
+/// Send a reminder where it was asked for, falling back to a direct message when the
+/// channel is gone or the bot can't talk in it anymore.
+async fn deliver(
+    http: &Http,
+    pool: &PgPool,
+    language_cache: &LanguageCache,
+    reminder: &Reminder,
+    now: DateTime<Utc>,
+) {
+    let lang = language_cache
+        .resolve(pool, reminder.guild_id, reminder.user_id)
+        .await;
+
+    let mut content = t!(
+        lang,
+        "reminder-message",
+        user = format!("<@{}>", reminder.user_id.0),
+        content = reminder.content.as_str()
+    );
+    if (now - reminder.due_at).num_seconds() > LATE_AFTER {
+        content.push('\n');
+        content.push_str(&t!(
+            lang,
+            "reminder-late",
+            time = format!("<t:{}:R>", reminder.due_at.timestamp())
+        ));
+    }
+    if let Some(next) = next_time(reminder, now) {
+        content.push('\n');
+        content.push_str(&t!(
+            lang,
+            "reminder-next",
+            time = format!("<t:{}:R>", next.timestamp()),
+            id = reminder.id
+        ));
+    }
+
+    let user_id = reminder.user_id;
+
+    if let Some(channel_id) = reminder.channel_id {
+        let result = channel_id
+            .send_message(http, |message| {
+                message
+                    .content(&content)
+                    .allowed_mentions(|mentions| mentions.empty_parse().users(vec![user_id]))
+            })
+            .await;
+
+        match result {
+            Ok(_) => return,
+            Err(why) => warn!(
+                "Couldn't deliver reminder {} in {}, trying a DM: {:?}",
+                reminder.id, channel_id, why
+            ),
+        }
+    }
+
+    let result = match user_id.create_dm_channel(http).await {
+        Ok(channel) => channel
+            .send_message(http, |message| {
+                message
+                    .content(&content)
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            })
+            .await
+            .map(|_| ()),
+        Err(why) => Err(why),
+    };
+
+    if let Err(why) = result {
+        warn!("Couldn't deliver reminder {}: {:?}", reminder.id, why);
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Commands and groups enabled or disabled per guild, channel or role.
+
+use crate::i18n::{t, Lang};
+use dashmap::DashMap;
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use sqlx::{query, PgPool};
+use std::sync::Arc;
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleTarget {
+    Guild,
+    Channel(ChannelId),
+    Role(RoleId),
+}
+
+impl RuleTarget {
+    fn scope(&self) -> &'static str {
+        match self {
+            RuleTarget::Guild => "guild",
+            RuleTarget::Channel(_) => "channel",
+            RuleTarget::Role(_) => "role",
+        }
+    }
+
+    fn target_id(&self, guild_id: GuildId) -> u64 {
+        match self {
+            RuleTarget::Guild => guild_id.0,
+            RuleTarget::Channel(channel_id) => channel_id.0,
+            RuleTarget::Role(role_id) => role_id.0,
+        }
+    }
+
+    pub fn mention(&self, lang: Lang) -> String {
+        match self {
+            RuleTarget::Guild => t!(lang, "rules-target-guild"),
+            RuleTarget::Channel(channel_id) => format!("<#{}>", channel_id.0),
+            RuleTarget::Role(role_id) => format!("<@&{}>", role_id.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub target: RuleTarget,
+    /// A command or a group name, always lowercase.
+    pub name: String,
+    pub enabled: bool,
+}
+
+#[derive(Default)]
+pub struct GuildRules {
+    pub rules: Vec<Rule>,
+    /// Don't tell members when they use a disabled command.
+    pub silent: bool,
+}
+
+impl GuildRules {
+    /// Decide if a command can be used, channel rules win over role rules which win over
+    /// guild rules, and a rule for the command wins over a rule for its group.
+    pub fn is_enabled(
+        &self,
+        command: &str,
+        group: &str,
+        channel_id: ChannelId,
+        roles: &[RoleId],
+    ) -> bool {
+        self.decide(command, group, |target| {
+            *target == RuleTarget::Channel(channel_id)
+        })
+        .or_else(|| {
+            self.decide(
+                command,
+                group,
+                |target| matches!(target, RuleTarget::Role(role_id) if roles.contains(role_id)),
+            )
+        })
+        .or_else(|| self.decide(command, group, |target| *target == RuleTarget::Guild))
+        .unwrap_or(true)
+    }
+
+    fn decide(
+        &self,
+        command: &str,
+        group: &str,
+        applies: impl Fn(&RuleTarget) -> bool,
+    ) -> Option<bool> {
+        for name in [command, group] {
+            let mut rules = self
+                .rules
+                .iter()
+                .filter(|rule| applies(&rule.target) && rule.name == name)
+                .peekable();
+
+            if rules.peek().is_some() {
+                // A member with several roles can use the command if any of them allows it
+                return Some(rules.any(|rule| rule.enabled));
+            }
+        }
+
+        None
+    }
+}
+
+/// Per-guild cache of the command rules, like the prefix cache.
+#[derive(Default)]
+pub struct RuleCache {
+    guilds: DashMap<GuildId, Arc<GuildRules>>,
+}
+
+impl RuleCache {
+    pub async fn get(&self, pool: &PgPool, guild_id: GuildId) -> Arc<GuildRules> {
+        if let Some(rules) = self.guilds.get(&guild_id) {
+            return rules.clone();
+        }
+
+        match load(pool, guild_id).await {
+            Ok(rules) => {
+                let rules = Arc::new(rules);
+                self.guilds.insert(guild_id, rules.clone());
+                rules
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next command retries the database
+                error!("Couldn't query database for command rules: {}", why);
+                Arc::new(GuildRules::default())
+            }
+        }
+    }
+
+    pub fn invalidate(&self, guild_id: GuildId) {
+        self.guilds.remove(&guild_id);
+    }
+}
+
+async fn load(pool: &PgPool, guild_id: GuildId) -> Result<GuildRules, sqlx::Error> {
+    let rows = query!(
+        "SELECT scope, target_id, name, enabled FROM command_rules WHERE guild_id=$1",
+        guild_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let silent = query!(
+        "SELECT silent_disabled FROM guilds WHERE guild_id=$1",
+        guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await?
+    .is_some_and(|row| row.silent_disabled);
+
+    let rules = rows
+        .into_iter()
+        .filter_map(|row| {
+            let target = match row.scope.as_str() {
+                "guild" => RuleTarget::Guild,
+                "channel" => RuleTarget::Channel(ChannelId(row.target_id as u64)),
+                "role" => RuleTarget::Role(RoleId(row.target_id as u64)),
+                _ => return None,
+            };
+
+            Some(Rule {
+                target,
+                name: row.name,
+                enabled: row.enabled,
+            })
+        })
+        .collect();
+
+    Ok(GuildRules { rules, silent })
+}
+
+/// Store a rule, `None` removes the rule so the command falls back to the broader rules.
+pub async fn set_rule(
+    pool: &PgPool,
+    guild_id: GuildId,
+    target: RuleTarget,
+    name: &str,
+    enabled: Option<bool>,
+) -> Result<(), sqlx::Error> {
+    let target_id = target.target_id(guild_id) as i64;
+
+    match enabled {
+        Some(enabled) => {
+            query!(
+                "INSERT INTO command_rules (guild_id, scope, target_id, name, enabled) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id, scope, target_id, name) DO UPDATE SET enabled = $5",
+                guild_id.0 as i64,
+                target.scope(),
+                target_id,
+                name,
+                enabled
+            )
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            query!(
+                "DELETE FROM command_rules WHERE guild_id=$1 AND scope=$2 AND target_id=$3 AND name=$4",
+                guild_id.0 as i64,
+                target.scope(),
+                target_id,
+                name
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn set_silent(pool: &PgPool, guild_id: GuildId, silent: bool) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO guilds (guild_id, silent_disabled) VALUES ($1, $2) ON CONFLICT (guild_id) DO UPDATE SET silent_disabled = $2",
+        guild_id.0 as i64,
+        silent
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNEL: RuleTarget = RuleTarget::Channel(ChannelId(10));
+    const OTHER_CHANNEL: RuleTarget = RuleTarget::Channel(ChannelId(11));
+    const ADMIN: RuleTarget = RuleTarget::Role(RoleId(20));
+    const MEMBER: RuleTarget = RuleTarget::Role(RoleId(21));
+    const GUILD: RuleTarget = RuleTarget::Guild;
+
+    /// The rules, the roles of the member and whether `blur` in the `image` group can be used.
+    type Case<'a> = (&'a [(RuleTarget, &'a str, bool)], &'a [RoleId], bool);
+
+    fn rules(rules: &[(RuleTarget, &str, bool)]) -> GuildRules {
+        GuildRules {
+            rules: rules
+                .iter()
+                .map(|(target, name, enabled)| Rule {
+                    target: *target,
+                    name: name.to_string(),
+                    enabled: *enabled,
+                })
+                .collect(),
+            silent: false,
+        }
+    }
+
+    #[test]
+    fn applies_rules_by_precedence() {
+        let cases: &[Case] = &[
+            (&[], &[], true),
+            (&[(GUILD, "blur", false)], &[], false),
+            (&[(GUILD, "image", false)], &[], false),
+            (&[(GUILD, "cat", false)], &[], true),
+            // The command wins over its group
+            (&[(GUILD, "image", false), (GUILD, "blur", true)], &[], true),
+            (
+                &[(GUILD, "image", true), (GUILD, "blur", false)],
+                &[],
+                false,
+            ),
+            // Roles win over the guild
+            (
+                &[(GUILD, "blur", false), (ADMIN, "blur", true)],
+                &[RoleId(20)],
+                true,
+            ),
+            (
+                &[(GUILD, "blur", false), (ADMIN, "blur", true)],
+                &[RoleId(21)],
+                false,
+            ),
+            // Any role allowing it is enough
+            (
+                &[(ADMIN, "blur", true), (MEMBER, "blur", false)],
+                &[RoleId(20), RoleId(21)],
+                true,
+            ),
+            // A role rule for the group still wins over a guild rule for the command
+            (
+                &[(GUILD, "blur", true), (MEMBER, "image", false)],
+                &[RoleId(21)],
+                false,
+            ),
+            // Channels win over roles and the guild
+            (
+                &[(ADMIN, "blur", true), (CHANNEL, "blur", false)],
+                &[RoleId(20)],
+                false,
+            ),
+            (
+                &[(GUILD, "image", false), (CHANNEL, "image", true)],
+                &[],
+                true,
+            ),
+            (
+                &[(GUILD, "blur", false), (OTHER_CHANNEL, "blur", true)],
+                &[],
+                false,
+            ),
+        ];
+
+        for (index, (list, roles, expected)) in cases.iter().enumerate() {
+            assert_eq!(
+                rules(list).is_enabled("blur", "image", ChannelId(10), roles),
+                *expected,
+                "case {}",
+                index
+            );
+        }
+    }
+}
@@ -18,41 +18,731 @@ use serde::Deserialize;
 use serenity::prelude::TypeMapKey;
 use std::{
     collections::HashMap,
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+    str::FromStr,
     sync::{Arc, Mutex},
 };
+use toml::Value;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub bot: BotSettings,
     pub logging: LoggingSettings,
     pub database: DatabaseSettings,
+    #[serde(default = "default_ratelimits")]
+    pub ratelimits: HashMap<String, BucketSettings>,
+    #[serde(default)]
+    pub features: FeatureSettings,
+    #[serde(default)]
+    pub images: ImageSettings,
+    pub presence: PresenceSettings,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct BotSettings {
     pub token: String,
+    #[serde(default)]
     pub application_id: u64,
+    pub error_log_channel: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct LoggingSettings {
     pub level: LevelFilter,
     pub filters: HashMap<String, LevelFilter>,
+    #[serde(default)]
+    pub format: LogFormat,
+    pub file: Option<PathBuf>,
+    #[serde(default)]
+    pub rotation: LogRotation,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct DatabaseSettings {
     pub url: String,
     pub max_connections: u32,
+    #[serde(default)]
+    pub prewarm_prefix_cache: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BucketScope {
+    #[default]
+    User,
+    Channel,
+    Guild,
+    Category,
+    Global,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct BucketSettings {
+    /// Seconds to wait between two invocations.
+    #[serde(default)]
+    pub delay: u64,
+    /// Seconds in which at most `limit` invocations are allowed.
+    #[serde(default)]
+    pub time_span: u64,
+    #[serde(default)]
+    pub limit: u32,
+    #[serde(default)]
+    pub scope: BucketScope,
+    /// How many ratelimited invocations are delayed instead of rejected.
+    #[serde(default)]
+    pub await_ratelimits: u32,
+    /// Skip this bucket in guilds marked as premium in the database.
+    #[serde(default)]
+    pub premium_exempt: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+pub struct FeatureSettings {
+    /// Commands which can't be used by anyone, as prefix or slash commands.
+    #[serde(default)]
+    pub disabled_commands: Vec<String>,
+}
+
+impl FeatureSettings {
+    pub fn is_disabled(&self, command: &str) -> bool {
+        self.disabled_commands
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(command))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ImageSettings {
+    /// Images processed at the same time.
+    pub max_jobs: usize,
+    /// Images waiting for a free job before the bot answers that it's busy.
+    pub max_queue: usize,
+    /// Seconds an image can take, waiting included.
+    pub timeout: u64,
+    /// Quality of the JPEGs sent back for JPEG images, from 1 to 100.
+    pub jpeg_quality: u8,
+    /// Frames an animated GIF can have and still be sent back animated.
+    pub max_frames: usize,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        ImageSettings {
+            max_jobs: 2,
+            max_queue: 8,
+            timeout: 20,
+            jpeg_quality: 85,
+            max_frames: 100,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct PresenceSettings {
+    #[serde(default)]
+    pub status: PresenceStatus,
+    /// Seconds between switching to the next activity.
+    pub interval: u64,
+    pub activities: Vec<ActivitySettings>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceStatus {
+    #[default]
+    Online,
+    Idle,
+    Dnd,
+    Invisible,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ActivitySettings {
+    #[serde(default)]
+    pub kind: ActivityKind,
+    /// Supports the `{guilds}`, `{users}`, `{shard}` and `{prefix}` placeholders.
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    Playing,
+    #[default]
+    Listening,
+    Watching,
+    Competing,
+}
+
+impl FromStr for ActivityKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "playing" => Ok(ActivityKind::Playing),
+            "listening" => Ok(ActivityKind::Listening),
+            "watching" => Ok(ActivityKind::Watching),
+            "competing" => Ok(ActivityKind::Competing),
+            _ => Err(format!(
+                "`{}` isn't an activity, use playing, listening, watching or competing.",
+                s
+            )),
+        }
+    }
+}
+
+/// Discord drops presence updates sent more often than this.
+pub const MIN_PRESENCE_INTERVAL: u64 = 15;
+
+/// Longest activity text Discord shows.
+pub const MAX_ACTIVITY_LENGTH: usize = 128;
+
+fn default_ratelimits() -> HashMap<String, BucketSettings> {
+    let mut ratelimits = HashMap::new();
+    ratelimits.insert(
+        "basic".to_string(),
+        BucketSettings {
+            delay: 0,
+            time_span: 5,
+            limit: 1,
+            scope: BucketScope::User,
+            await_ratelimits: 0,
+            premium_exempt: false,
+        },
+    );
+    ratelimits.insert(
+        "image".to_string(),
+        BucketSettings {
+            delay: 0,
+            time_span: 30,
+            limit: 3,
+            scope: BucketScope::Guild,
+            await_ratelimits: 0,
+            premium_exempt: true,
+        },
+    );
+    ratelimits
 }
 
 impl TypeMapKey for Settings {
     type Value = Arc<Mutex<Settings>>;
 }
 
-pub fn init() -> Settings {
-    let config = std::fs::read_to_string("config.toml")
-        .expect("Something went wrong while trying to parse the configuration file");
-    toml::from_str(&config)
-        .expect("Something went wrong while trying to deserialize the configuration file.")
+/// Built-in values, overridden by the config file, the environment and secret files.
+const DEFAULTS: &str = r#"
+[bot]
+token = ""
+
+[logging]
+level = "WARN"
+
+    [logging.filters]
+    rustic = "INFO"
+
+[database]
+url             = ""
+max_connections = 5
+
+[presence]
+interval = 60
+
+    [[presence.activities]]
+    kind = "listening"
+    text = "@Rustic help"
+"#;
+
+/// Prefix of environment variables overriding settings, `RUSTIC__BOT__TOKEN` sets `bot.token`.
+const ENV_PREFIX: &str = "RUSTIC__";
+
+/// Keys which can be read from a file instead, `bot.token_file` fills `bot.token`.
+const SECRETS: &[(&str, &str)] = &[("bot", "token"), ("database", "url")];
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Secret(String, PathBuf, io::Error),
+    Env(String, String),
+    Invalid(String, String),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Read(path, why) => {
+                write!(
+                    f,
+                    "Couldn't read the config file {}: {}",
+                    path.display(),
+                    why
+                )
+            }
+            SettingsError::Parse(path, why) => {
+                write!(
+                    f,
+                    "Couldn't parse the config file {}: {}",
+                    path.display(),
+                    why
+                )
+            }
+            SettingsError::Secret(key, path, why) => write!(
+                f,
+                "Couldn't read `{}` from the file {}: {}",
+                key,
+                path.display(),
+                why
+            ),
+            SettingsError::Env(var, why) => {
+                write!(f, "Invalid environment variable {}: {}", var, why)
+            }
+            SettingsError::Invalid(key, why) => write!(f, "Invalid setting `{}`: {}", key, why),
+        }
+    }
+}
+
+impl Error for SettingsError {}
+
+/// The config file given with `--config <path>` or `RUSTIC_CONFIG`, `config.toml` otherwise.
+pub fn config_path() -> (PathBuf, bool) {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            if let Some(path) = args.next() {
+                return (PathBuf::from(path), true);
+            }
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            return (PathBuf::from(path), true);
+        }
+    }
+
+    match std::env::var_os("RUSTIC_CONFIG") {
+        Some(path) => (PathBuf::from(path), true),
+        None => (PathBuf::from("config.toml"), false),
+    }
+}
+
+/// Recursively merge `overlay` into `base`, overlay values win.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Table(base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Parse an environment variable, keeping it a string if the setting it replaces is one.
+fn parse_env_value(raw: &str, existing: Option<&Value>) -> Value {
+    if let Some(Value::String(_)) = existing {
+        return Value::String(raw.to_string());
+    }
+
+    if let Ok(Value::Table(mut table)) = format!("value = {}", raw).parse::<Value>() {
+        if let Some(value) = table.remove("value") {
+            return value;
+        }
+    }
+
+    Value::String(raw.to_string())
+}
+
+fn apply_env(config: &mut Value) -> Result<(), SettingsError> {
+    apply_vars(config, std::env::vars())
+}
+
+fn apply_vars(
+    config: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), SettingsError> {
+    for (var, raw) in vars {
+        let path = match var.strip_prefix(ENV_PREFIX) {
+            Some(path) => path.to_lowercase(),
+            None => continue,
+        };
+        let keys = path.split("__").collect::<Vec<&str>>();
+
+        let mut table = &mut *config;
+        for key in &keys[..keys.len() - 1] {
+            table = match table {
+                Value::Table(map) => map
+                    .entry(key.to_string())
+                    .or_insert_with(|| Value::Table(Default::default())),
+                _ => {
+                    return Err(SettingsError::Env(
+                        var.clone(),
+                        format!("`{}` is not a section", key),
+                    ))
+                }
+            };
+        }
+
+        match table {
+            Value::Table(map) => {
+                let key = keys[keys.len() - 1].to_string();
+                let value = parse_env_value(&raw, map.get(&key));
+                map.insert(key, value);
+            }
+            _ => return Err(SettingsError::Env(var, "not a section".to_string())),
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_secrets(config: &mut Value) -> Result<(), SettingsError> {
+    for (section, key) in SECRETS {
+        let table = match config.get_mut(section) {
+            Some(Value::Table(table)) => table,
+            _ => continue,
+        };
+
+        let path = match table.remove(&format!("{}_file", key)) {
+            Some(Value::String(path)) => PathBuf::from(path),
+            Some(_) => {
+                return Err(SettingsError::Invalid(
+                    format!("{}.{}_file", section, key),
+                    "expected a path".to_string(),
+                ))
+            }
+            None => continue,
+        };
+
+        let secret = std::fs::read_to_string(&path).map_err(|why| {
+            SettingsError::Secret(format!("{}.{}", section, key), path.clone(), why)
+        })?;
+        table.insert(key.to_string(), Value::String(secret.trim().to_string()));
+    }
+
+    Ok(())
+}
+
+fn validate(settings: &Settings) -> Result<(), SettingsError> {
+    if settings.bot.token.is_empty() {
+        return Err(SettingsError::Invalid(
+            "bot.token".to_string(),
+            "missing, set it in the config file, RUSTIC__BOT__TOKEN or bot.token_file".to_string(),
+        ));
+    }
+
+    if settings.bot.application_id == 0 {
+        return Err(SettingsError::Invalid(
+            "bot.application_id".to_string(),
+            "missing, copy it from the Discord developer portal".to_string(),
+        ));
+    }
+
+    if settings.database.url.is_empty() {
+        return Err(SettingsError::Invalid(
+            "database.url".to_string(),
+            "missing, set it in the config file, RUSTIC__DATABASE__URL or database.url_file"
+                .to_string(),
+        ));
+    }
+
+    if settings.database.max_connections == 0 {
+        return Err(SettingsError::Invalid(
+            "database.max_connections".to_string(),
+            "has to be at least 1".to_string(),
+        ));
+    }
+
+    for (name, bucket) in &settings.ratelimits {
+        if bucket.limit == 0 && bucket.delay == 0 {
+            return Err(SettingsError::Invalid(
+                format!("ratelimits.{}", name),
+                "needs a `limit` or a `delay`".to_string(),
+            ));
+        }
+    }
+
+    if settings.images.max_jobs == 0 {
+        return Err(SettingsError::Invalid(
+            "images.max_jobs".to_string(),
+            "has to be at least 1".to_string(),
+        ));
+    }
+
+    if settings.images.timeout == 0 {
+        return Err(SettingsError::Invalid(
+            "images.timeout".to_string(),
+            "has to be at least 1 second".to_string(),
+        ));
+    }
+
+    if !(1..=100).contains(&settings.images.jpeg_quality) {
+        return Err(SettingsError::Invalid(
+            "images.jpeg_quality".to_string(),
+            "has to be from 1 to 100".to_string(),
+        ));
+    }
+
+    if settings.images.max_frames == 0 {
+        return Err(SettingsError::Invalid(
+            "images.max_frames".to_string(),
+            "has to be at least 1".to_string(),
+        ));
+    }
+
+    if settings.presence.interval < MIN_PRESENCE_INTERVAL {
+        return Err(SettingsError::Invalid(
+            "presence.interval".to_string(),
+            format!("has to be at least {} seconds", MIN_PRESENCE_INTERVAL),
+        ));
+    }
+
+    if settings.presence.activities.is_empty() {
+        return Err(SettingsError::Invalid(
+            "presence.activities".to_string(),
+            "needs at least one activity".to_string(),
+        ));
+    }
+
+    for (index, activity) in settings.presence.activities.iter().enumerate() {
+        if activity.text.trim().is_empty() || activity.text.chars().count() > MAX_ACTIVITY_LENGTH {
+            return Err(SettingsError::Invalid(
+                format!("presence.activities[{}].text", index),
+                format!("has to be 1 to {} characters", MAX_ACTIVITY_LENGTH),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the settings from the defaults, the config file, the environment and secret files.
+pub fn load(path: &Path, required: bool) -> Result<Settings, SettingsError> {
+    let mut config = DEFAULTS.parse::<Value>().unwrap();
+
+    match std::fs::read_to_string(path) {
+        Ok(file) => {
+            let file = file
+                .parse::<Value>()
+                .map_err(|why| SettingsError::Parse(path.to_path_buf(), why))?;
+            merge(&mut config, file);
+        }
+        Err(why) if required || why.kind() != io::ErrorKind::NotFound => {
+            return Err(SettingsError::Read(path.to_path_buf(), why))
+        }
+        Err(_) => {}
+    }
+
+    apply_env(&mut config)?;
+    apply_secrets(&mut config)?;
+
+    from_value(config)
+}
+
+/// Deserialize the merged layers and check the result.
+fn from_value(config: Value) -> Result<Settings, SettingsError> {
+    let mut settings: Settings = serde_path_to_error::deserialize(config).map_err(|why| {
+        let key = why.path().to_string();
+        SettingsError::Invalid(key, why.into_inner().to_string())
+    })?;
+
+    // Commands reference these buckets, so they always have to exist
+    for (name, bucket) in default_ratelimits() {
+        settings.ratelimits.entry(name).or_insert(bucket);
+    }
+
+    validate(&settings)?;
+
+    Ok(settings)
+}
+
+pub fn init() -> Result<Settings, SettingsError> {
+    let (path, required) = config_path();
+    load(&path, required)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The smallest config file that passes validation.
+    const FILE: &str = r#"
+[bot]
+token = "file-token"
+application_id = 1
+
+[database]
+url = "postgres://localhost/rustic"
+"#;
+
+    fn layers(file: &str, vars: &[(&str, &str)]) -> Result<Value, SettingsError> {
+        let mut config = DEFAULTS.parse::<Value>().unwrap();
+        merge(&mut config, file.parse::<Value>().unwrap());
+        apply_vars(
+            &mut config,
+            vars.iter()
+                .map(|(var, value)| (var.to_string(), value.to_string())),
+        )?;
+        apply_secrets(&mut config)?;
+
+        Ok(config)
+    }
+
+    fn settings(file: &str, vars: &[(&str, &str)]) -> Result<Settings, SettingsError> {
+        from_value(layers(file, vars)?)
+    }
+
+    fn invalid_key(result: Result<Settings, SettingsError>) -> String {
+        match result {
+            Err(SettingsError::Invalid(key, _)) => key,
+            Err(why) => panic!("unexpected error: {}", why),
+            Ok(_) => panic!("the settings were accepted"),
+        }
+    }
+
+    fn secret_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rustic-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn merges_tables_recursively() {
+        let mut base = "[a]\nx = 1\ny = 2".parse::<Value>().unwrap();
+        merge(&mut base, "[a]\ny = 3\nz = 4".parse::<Value>().unwrap());
+
+        assert_eq!(base, "[a]\nx = 1\ny = 3\nz = 4".parse::<Value>().unwrap());
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let settings = settings(FILE, &[]).unwrap();
+        assert_eq!(settings.bot.token, "file-token");
+        // Defaults fill in what the file leaves out
+        assert_eq!(settings.database.max_connections, 5);
+        assert_eq!(settings.images, ImageSettings::default());
+        assert!(settings.ratelimits.contains_key("image"));
+
+        let settings = self::settings(
+            FILE,
+            &[
+                ("RUSTIC__BOT__TOKEN", "env-token"),
+                ("RUSTIC__DATABASE__MAX_CONNECTIONS", "12"),
+                ("OTHER__BOT__TOKEN", "ignored"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(settings.bot.token, "env-token");
+        assert_eq!(settings.database.max_connections, 12);
+    }
+
+    #[test]
+    fn reads_nested_env_keys() {
+        let settings = settings(
+            FILE,
+            &[
+                ("RUSTIC__IMAGES__JPEG_QUALITY", "70"),
+                ("RUSTIC__LOGGING__FILTERS__SERENITY", "DEBUG"),
+                ("RUSTIC__RATELIMITS__TAGS__LIMIT", "2"),
+                ("RUSTIC__RATELIMITS__TAGS__TIME_SPAN", "10"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(settings.images.jpeg_quality, 70);
+        assert_eq!(settings.images.max_jobs, 2);
+        assert_eq!(
+            settings.logging.filters.get("serenity"),
+            Some(&LevelFilter::Debug)
+        );
+        assert_eq!(settings.ratelimits["tags"].limit, 2);
+    }
+
+    #[test]
+    fn keeps_string_settings_as_strings() {
+        let settings = settings(FILE, &[("RUSTIC__BOT__TOKEN", "12345")]).unwrap();
+        assert_eq!(settings.bot.token, "12345");
+    }
+
+    #[test]
+    fn refuses_env_keys_inside_values() {
+        let result = layers(FILE, &[("RUSTIC__BOT__TOKEN__PART", "x")]);
+        assert!(matches!(result, Err(SettingsError::Env(..))));
+    }
+
+    #[test]
+    fn reads_trimmed_secret_files() {
+        let path = secret_file("token", "  secret-token\n\n");
+        let settings = settings(
+            FILE,
+            &[
+                ("RUSTIC__BOT__TOKEN", "env-token"),
+                ("RUSTIC__BOT__TOKEN_FILE", path.to_str().unwrap()),
+            ],
+        )
+        .unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(settings.bot.token, "secret-token");
+    }
+
+    #[test]
+    fn fails_on_missing_secret_files() {
+        let file = format!("{}\nurl_file = \"/nonexistent/rustic-database-url\"", FILE);
+        let result = layers(&file, &[]);
+
+        assert!(matches!(result, Err(SettingsError::Secret(key, ..)) if key == "database.url"));
+    }
+
+    #[test]
+    fn validates_settings() {
+        assert_eq!(invalid_key(settings("", &[])), "bot.token");
+        assert_eq!(
+            invalid_key(settings(FILE, &[("RUSTIC__IMAGES__JPEG_QUALITY", "0")])),
+            "images.jpeg_quality"
+        );
+        assert_eq!(
+            invalid_key(settings(FILE, &[("RUSTIC__PRESENCE__INTERVAL", "5")])),
+            "presence.interval"
+        );
+        assert_eq!(
+            invalid_key(settings(
+                FILE,
+                &[
+                    ("RUSTIC__RATELIMITS__BASIC__LIMIT", "0"),
+                    ("RUSTIC__RATELIMITS__BASIC__DELAY", "0"),
+                ]
+            )),
+            "ratelimits.basic"
+        );
+    }
+
+    #[test]
+    fn reports_the_path_of_badly_typed_settings() {
+        assert_eq!(
+            invalid_key(settings(FILE, &[("RUSTIC__IMAGES__MAX_JOBS", "many")])),
+            "images.max_jobs"
+        );
+    }
 }
@@ -0,0 +1,201 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use sqlx::{query, PgPool};
+
+pub struct Tag {
+    pub name: String,
+    pub content: String,
+    pub owner_id: UserId,
+    pub uses: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+pub async fn get(pool: &PgPool, guild_id: GuildId, name: &str) -> Result<Option<Tag>, sqlx::Error> {
+    let row = query!(
+        "SELECT name, content, owner_id, uses, created_at, updated_at FROM tags WHERE guild_id=$1 AND name=$2",
+        guild_id.0 as i64,
+        name
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| Tag {
+        name: row.name,
+        content: row.content,
+        owner_id: UserId(row.owner_id as u64),
+        uses: row.uses,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    }))
+}
+
+/// Get the content of a tag and count the use.
+pub async fn use_tag(
+    pool: &PgPool,
+    guild_id: GuildId,
+    name: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let row = query!(
+        "UPDATE tags SET uses = uses + 1 WHERE guild_id=$1 AND name=$2 RETURNING content",
+        guild_id.0 as i64,
+        name
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| row.content))
+}
+
+/// Create a tag, returning false if the name is already taken.
+pub async fn create(
+    pool: &PgPool,
+    guild_id: GuildId,
+    name: &str,
+    content: &str,
+    owner_id: UserId,
+) -> Result<bool, sqlx::Error> {
+    let result = query!(
+        "INSERT INTO tags (guild_id, name, content, owner_id) VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, name) DO NOTHING",
+        guild_id.0 as i64,
+        name,
+        content,
+        owner_id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn edit(
+    pool: &PgPool,
+    guild_id: GuildId,
+    name: &str,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "UPDATE tags SET content = $3, updated_at = NOW() WHERE guild_id=$1 AND name=$2",
+        guild_id.0 as i64,
+        name,
+        content
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete(pool: &PgPool, guild_id: GuildId, name: &str) -> Result<(), sqlx::Error> {
+    query!(
+        "DELETE FROM tags WHERE guild_id=$1 AND name=$2",
+        guild_id.0 as i64,
+        name
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn transfer(
+    pool: &PgPool,
+    guild_id: GuildId,
+    name: &str,
+    owner_id: UserId,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "UPDATE tags SET owner_id = $3 WHERE guild_id=$1 AND name=$2",
+        guild_id.0 as i64,
+        name,
+        owner_id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn count(pool: &PgPool, guild_id: GuildId) -> Result<i64, sqlx::Error> {
+    let row = query!(
+        r#"SELECT COUNT(*) AS "count!" FROM tags WHERE guild_id=$1"#,
+        guild_id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.count)
+}
+
+/// The names of the tags of a guild, or only the ones a member owns, most used first.
+pub async fn list(
+    pool: &PgPool,
+    guild_id: GuildId,
+    owner_id: Option<UserId>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let names = match owner_id {
+        Some(owner_id) => query!(
+            "SELECT name FROM tags WHERE guild_id=$1 AND owner_id=$2 ORDER BY uses DESC, name",
+            guild_id.0 as i64,
+            owner_id.0 as i64
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| row.name)
+        .collect(),
+        None => query!(
+            "SELECT name FROM tags WHERE guild_id=$1 ORDER BY uses DESC, name",
+            guild_id.0 as i64
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| row.name)
+        .collect(),
+    };
+
+    Ok(names)
+}
+
+pub async fn search(
+    pool: &PgPool,
+    guild_id: GuildId,
+    text: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = query!(
+        "SELECT name FROM tags WHERE guild_id=$1 AND strpos(name, $2) > 0 ORDER BY uses DESC, name LIMIT 25",
+        guild_id.0 as i64,
+        text
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.name).collect())
+}
+
+/// Fill in the variables of a tag and defuse mass and role mentions.
+pub fn render(content: &str, user_id: UserId, channel_id: ChannelId, args: &str) -> String {
+    content
+        .replace("{user}", &format!("<@{}>", user_id.0))
+        .replace("{channel}", &format!("<#{}>", channel_id.0))
+        .replace("{args}", args)
+        .replace("@everyone", "@\u{200B}everyone")
+        .replace("@here", "@\u{200B}here")
+        .replace("<@&", "<@\u{200B}&")
+}
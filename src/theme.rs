@@ -0,0 +1,270 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-guild embed themes, every embed the bot sends is built from one.
+
+use crate::{PgPoolContainer, ThemeCacheContainer};
+use dashmap::DashMap;
+use serenity::{builder::CreateEmbed, model::id::GuildId, prelude::Context};
+use sqlx::{query, PgPool};
+use std::sync::Arc;
+use tracing::error;
+
+/// The colour of the bot's embeds when a guild hasn't picked its own.
+pub const DEFAULT_COLOUR: u32 = 0xF05B4A;
+
+/// Which colour of the theme an embed uses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Primary,
+    Error,
+    Success,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub primary: u32,
+    pub error: u32,
+    pub success: u32,
+    pub footer: Option<String>,
+    pub author_name: Option<String>,
+    /// Only shown together with the author name, Discord needs both.
+    pub author_icon: Option<String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            primary: DEFAULT_COLOUR,
+            error: DEFAULT_COLOUR,
+            success: DEFAULT_COLOUR,
+            footer: None,
+            author_name: None,
+            author_icon: None,
+        }
+    }
+}
+
+impl Theme {
+    pub fn colour(&self, style: Style) -> u32 {
+        match style {
+            Style::Primary => self.primary,
+            Style::Error => self.error,
+            Style::Success => self.success,
+        }
+    }
+
+    /// Start an embed in the guild's colours, a footer or author set by the command
+    /// afterwards replaces the one of the theme.
+    pub fn embed(&self, style: Style) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed.color(self.colour(style));
+
+        if let Some(footer) = &self.footer {
+            embed.footer(|f| f.text(footer));
+        }
+
+        if let Some(name) = &self.author_name {
+            embed.author(|author| {
+                author.name(name);
+                if let Some(icon) = &self.author_icon {
+                    author.icon_url(icon);
+                }
+                author
+            });
+        }
+
+        embed
+    }
+}
+
+/// The colours of Discord's palette, which can be used by name.
+const NAMED_COLOURS: &[(&str, u32)] = &[
+    ("blurple", 0x5865F2),
+    ("green", 0x57F287),
+    ("yellow", 0xFEE75C),
+    ("fuchsia", 0xEB459E),
+    ("red", 0xED4245),
+    ("white", 0xFFFFFF),
+    ("black", 0x000000),
+];
+
+/// Parse a colour written as `#F05B4A`, `0xF05B4A`, `F05B4A` or by name, like `blurple`.
+pub fn parse_colour(text: &str) -> Option<u32> {
+    if let Some((_, colour)) = NAMED_COLOURS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text.trim()))
+    {
+        return Some(*colour);
+    }
+
+    let hex = text
+        .trim()
+        .trim_start_matches('#')
+        .trim_start_matches("0x")
+        .trim_start_matches("0X");
+
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u32::from_str_radix(hex, 16).ok()
+}
+
+pub fn format_colour(colour: u32) -> String {
+    format!("#{:06X}", colour)
+}
+
+/// Per-guild cache of the themes, like the prefix cache.
+#[derive(Default)]
+pub struct ThemeCache {
+    guilds: DashMap<GuildId, Arc<Theme>>,
+}
+
+impl ThemeCache {
+    pub async fn get(&self, pool: &PgPool, guild_id: GuildId) -> Arc<Theme> {
+        if let Some(theme) = self.guilds.get(&guild_id) {
+            return theme.clone();
+        }
+
+        match load(pool, guild_id).await {
+            Ok(theme) => {
+                let theme = Arc::new(theme);
+                self.guilds.insert(guild_id, theme.clone());
+                theme
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next command retries the database
+                error!("Couldn't query database for the theme: {}", why);
+                Arc::new(Theme::default())
+            }
+        }
+    }
+
+    pub fn invalidate(&self, guild_id: GuildId) {
+        self.guilds.remove(&guild_id);
+    }
+}
+
+async fn load(pool: &PgPool, guild_id: GuildId) -> Result<Theme, sqlx::Error> {
+    let row = query!(
+        "SELECT theme_primary, theme_error, theme_success, theme_footer, theme_author_name, theme_author_icon FROM guilds WHERE guild_id=$1",
+        guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let row = match row {
+        Some(row) => row,
+        None => return Ok(Theme::default()),
+    };
+
+    Ok(Theme {
+        primary: row
+            .theme_primary
+            .map_or(DEFAULT_COLOUR, |colour| colour as u32),
+        error: row
+            .theme_error
+            .map_or(DEFAULT_COLOUR, |colour| colour as u32),
+        success: row
+            .theme_success
+            .map_or(DEFAULT_COLOUR, |colour| colour as u32),
+        footer: row.theme_footer,
+        author_name: row.theme_author_name,
+        author_icon: row.theme_author_icon,
+    })
+}
+
+/// The theme of a guild, direct messages always use the default one.
+pub async fn theme(ctx: &Context, guild_id: Option<GuildId>) -> Arc<Theme> {
+    let guild_id = match guild_id {
+        Some(guild_id) => guild_id,
+        None => return Arc::new(Theme::default()),
+    };
+
+    let (pool, theme_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<ThemeCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    theme_cache.get(&pool, guild_id).await
+}
+
+/// Default colours are stored as NULL so they follow the default if it ever changes.
+fn stored_colour(colour: u32) -> Option<i32> {
+    (colour != DEFAULT_COLOUR).then_some(colour as i32)
+}
+
+pub async fn set_theme(pool: &PgPool, guild_id: GuildId, theme: &Theme) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO guilds (guild_id, theme_primary, theme_error, theme_success, theme_footer, theme_author_name, theme_author_icon) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (guild_id) DO UPDATE SET theme_primary = $2, theme_error = $3, theme_success = $4, theme_footer = $5, theme_author_name = $6, theme_author_icon = $7",
+        guild_id.0 as i64,
+        stored_colour(theme.primary),
+        stored_colour(theme.error),
+        stored_colour(theme.success),
+        theme.footer,
+        theme.author_name,
+        theme.author_icon
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(parse_colour("F05B4A"), Some(0xF05B4A));
+        assert_eq!(parse_colour("#f05b4a"), Some(0xF05B4A));
+        assert_eq!(parse_colour("0xF05B4A"), Some(0xF05B4A));
+        assert_eq!(parse_colour(" #000000 "), Some(0));
+    }
+
+    #[test]
+    fn parses_named_colours() {
+        assert_eq!(parse_colour("blurple"), Some(0x5865F2));
+        assert_eq!(parse_colour("Red"), Some(0xED4245));
+    }
+
+    #[test]
+    fn rejects_other_colours() {
+        for text in [
+            "",
+            "#",
+            "#F05B4",
+            "#F05B4AA",
+            "#GGGGGG",
+            "+F05B4A",
+            "orange",
+            "0x#F05B4A",
+        ] {
+            assert_eq!(parse_colour(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn formats_colours_the_way_they_are_parsed() {
+        assert_eq!(format_colour(0x5865F2), "#5865F2");
+        assert_eq!(format_colour(0xFF), "#0000FF");
+        assert_eq!(parse_colour(&format_colour(0xF05B4A)), Some(0xF05B4A));
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The timezone each user picked, from the IANA database built into `chrono-tz`.
+
+use crate::{PgPoolContainer, TimezoneCacheContainer};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use dashmap::DashMap;
+use serenity::{model::id::UserId, prelude::Context};
+use sqlx::{query, PgPool};
+use std::collections::HashSet;
+use tracing::error;
+
+/// How many timezones to suggest when nothing matched well enough.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Per-user cache of the chosen timezones, like the language cache.
+#[derive(Default)]
+pub struct TimezoneCache {
+    users: DashMap<UserId, Option<Tz>>,
+}
+
+impl TimezoneCache {
+    pub async fn get(&self, pool: &PgPool, user_id: UserId) -> Option<Tz> {
+        if let Some(timezone) = self.users.get(&user_id) {
+            return *timezone;
+        }
+
+        match query!(
+            "SELECT timezone FROM users WHERE user_id=$1",
+            user_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await
+        {
+            Ok(row) => {
+                let timezone = row
+                    .and_then(|row| row.timezone)
+                    .and_then(|name| name.parse::<Tz>().ok());
+                self.users.insert(user_id, timezone);
+                timezone
+            }
+            Err(why) => {
+                // Don't cache the fallback so the next command retries the database
+                error!("Couldn't query database for the user timezone: {}", why);
+                None
+            }
+        }
+    }
+
+    pub fn invalidate(&self, user_id: UserId) {
+        self.users.remove(&user_id);
+    }
+}
+
+/// The timezone a user picked, if any.
+pub async fn timezone(ctx: &Context, user_id: UserId) -> Option<Tz> {
+    let (pool, timezone_cache) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<PgPoolContainer>().unwrap().clone(),
+            data.get::<TimezoneCacheContainer>().unwrap().clone(),
+        )
+    };
+
+    timezone_cache.get(&pool, user_id).await
+}
+
+pub async fn set_timezone(
+    pool: &PgPool,
+    user_id: UserId,
+    timezone: Option<Tz>,
+) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO users (user_id, timezone) VALUES ($1, $2) ON CONFLICT (user_id) DO UPDATE SET timezone = $2",
+        user_id.0 as i64,
+        timezone.map(|timezone| timezone.name())
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Lowercase with spaces and dashes as underscores, like the city part of an IANA name.
+fn normalize(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_")
+}
+
+fn city(timezone: &Tz) -> String {
+    normalize(timezone.name().rsplit('/').next().unwrap_or_default())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b) in b.iter().enumerate() {
+            let cost = usize::from(a != *b);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Find a timezone by its IANA name, its city, or a close spelling of the city. When
+/// nothing is close enough the best guesses are returned instead.
+pub fn find_timezone(text: &str) -> Result<Tz, Vec<Tz>> {
+    let text = normalize(text);
+
+    if text.is_empty() {
+        return Err(Vec::new());
+    }
+
+    if let Some(timezone) = TZ_VARIANTS
+        .iter()
+        .find(|timezone| normalize(timezone.name()) == text)
+        .or_else(|| TZ_VARIANTS.iter().find(|timezone| city(timezone) == text))
+    {
+        return Ok(*timezone);
+    }
+
+    let mut candidates = TZ_VARIANTS
+        .iter()
+        .map(|timezone| {
+            let city = city(timezone);
+            // A city starting with the text is as good as a typo
+            let distance = if text.len() >= 3 && city.starts_with(&text) {
+                1
+            } else {
+                edit_distance(&text, &city)
+            };
+            (distance, *timezone)
+        })
+        .filter(|(distance, _)| *distance <= text.len() / 2)
+        .collect::<Vec<(usize, Tz)>>();
+    candidates.sort_by_key(|(distance, timezone)| (*distance, timezone.name().len()));
+    // Links like `Singapore` and `Asia/Singapore` are the same timezone
+    let mut cities = HashSet::new();
+    candidates.retain(|(_, timezone)| cities.insert(city(timezone)));
+
+    match candidates.as_slice() {
+        [(best, timezone), rest @ ..]
+            if *best <= (text.len() / 4).max(1)
+                && rest.first().is_none_or(|(next, _)| next > best) =>
+        {
+            Ok(*timezone)
+        }
+        _ => Err(candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, timezone)| timezone)
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America, Asia, Europe};
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(edit_distance("berlin", "berlin"), 0);
+        assert_eq!(edit_distance("berlln", "berlin"), 1);
+        assert_eq!(edit_distance("tokio", "tokyo"), 1);
+        assert_eq!(edit_distance("", "oslo"), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn finds_iana_names() {
+        assert_eq!(find_timezone("Europe/London"), Ok(Europe::London));
+        assert_eq!(find_timezone("  europe/london "), Ok(Europe::London));
+        assert_eq!(find_timezone("UTC"), Ok(Tz::UTC));
+    }
+
+    #[test]
+    fn finds_cities() {
+        assert_eq!(find_timezone("tokyo"), Ok(Asia::Tokyo));
+        assert_eq!(find_timezone("Sao Paulo"), Ok(America::Sao_Paulo));
+        assert_eq!(find_timezone("new-york"), Ok(America::New_York));
+    }
+
+    #[test]
+    fn finds_cities_with_a_typo() {
+        assert_eq!(find_timezone("berlln"), Ok(Europe::Berlin));
+        assert_eq!(find_timezone("tokio"), Ok(Asia::Tokyo));
+    }
+
+    #[test]
+    fn suggests_timezones_for_ambiguous_text() {
+        let suggestions = find_timezone("san").unwrap_err();
+
+        assert!(!suggestions.is_empty());
+        assert!(suggestions.len() <= MAX_SUGGESTIONS);
+        assert!(suggestions
+            .iter()
+            .all(|timezone| city(timezone).starts_with("san")));
+    }
+
+    #[test]
+    fn refuses_empty_and_unknown_text() {
+        assert_eq!(find_timezone(""), Err(Vec::new()));
+        assert_eq!(find_timezone("   "), Err(Vec::new()));
+        assert_eq!(find_timezone("qwertyuiop"), Err(Vec::new()));
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright (C) 2021 MrArkon
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+
+/// Parse a duration like `30m`, `2h30m` or `7d`, units are s, m, h, d and w.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number = String::new();
+
+    for c in text.trim().to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        // Keeps every unit below the range chrono panics at
+        let value = number
+            .parse::<i64>()
+            .ok()
+            .filter(|value| *value <= 1_000_000)?;
+        number.clear();
+
+        let unit = match c {
+            's' => Duration::seconds(value),
+            'm' => Duration::minutes(value),
+            'h' => Duration::hours(value),
+            'd' => Duration::days(value),
+            'w' => Duration::weeks(value),
+            _ => return None,
+        };
+        total = total.checked_add(&unit)?;
+    }
+
+    if !number.is_empty() || total.is_zero() {
+        return None;
+    }
+
+    Some(total)
+}
+
+/// Write a duration the way `parse_duration` reads it, like `1d 2h 30m`.
+pub fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.num_seconds().max(0);
+    let mut parts = Vec::new();
+
+    for (unit, length) in [
+        ("w", 604_800),
+        ("d", 86_400),
+        ("h", 3_600),
+        ("m", 60),
+        ("s", 1),
+    ] {
+        if seconds >= length {
+            parts.push(format!("{}{}", seconds / length, unit));
+            seconds %= length;
+        }
+    }
+
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Parse a time of day like `9am`, `9:30pm` or `21:30`.
+fn parse_time_of_day(text: &str) -> Option<NaiveTime> {
+    let text = text.to_lowercase();
+    let (clock, offset) = if let Some(clock) = text.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = text.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (text.as_str(), None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => {
+            (hour.parse::<u32>().ok()?, minute.parse().ok()?)
+        }
+        // A bare number is more likely part of the text than a time
+        None if offset.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// A local time in `tz`. A time skipped when the clocks jump forward is moved past the gap,
+/// so 2:30 on a night going from 2:00 straight to 3:00 becomes 3:30.
+fn local_to_utc<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    if let Some(time) = tz.from_local_datetime(&local).earliest() {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    // Read the time with the offset from before the jump
+    let before = tz
+        .from_local_datetime(&(local - Duration::days(1)))
+        .earliest()?;
+    let offset = Duration::seconds(i64::from(before.offset().fix().local_minus_utc()));
+
+    Some(Utc.from_utc_datetime(&(local - offset)))
+}
+
+/// Parse when something should happen from the start of `words`: `in 2h30m`, `tomorrow 9am`,
+/// `17:30`, `2021-12-24 18:00` and so on, times of day are read in `tz`. Returns the time and
+/// how many words it took up.
+pub fn parse_when<Tz: TimeZone>(
+    words: &[&str],
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Option<(DateTime<Utc>, usize)> {
+    let words = words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>();
+    let word = |index: usize| words.get(index).map(String::as_str);
+
+    let mut used = usize::from(word(0) == Some("in"));
+    let mut total = Duration::zero();
+    let mut durations = 0;
+
+    while let Some(duration) = word(used + durations).and_then(parse_duration) {
+        total = total.checked_add(&duration)?;
+        durations += 1;
+    }
+
+    if durations > 0 {
+        return Some((now.checked_add_signed(total)?, used + durations));
+    } else if used > 0 {
+        return None;
+    }
+
+    let local_now = now.with_timezone(tz).naive_local();
+    let date = match word(0)? {
+        "today" => Some(local_now.date()),
+        "tomorrow" => local_now.date().succ_opt(),
+        word => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+    };
+
+    if date.is_some() {
+        used += 1;
+    }
+
+    let at = usize::from(word(used) == Some("at"));
+    let time = word(used + at).and_then(parse_time_of_day);
+    let time = match (time, date) {
+        (Some(time), _) => {
+            used += at + 1;
+            time
+        }
+        // Only a day, keep the current time of day
+        (None, Some(_)) if at == 0 => local_now.time(),
+        _ => return None,
+    };
+
+    let day_given = date.is_some();
+    let mut date = date.unwrap_or_else(|| local_now.date());
+    let mut due = local_to_utc(tz, date.and_time(time))?;
+
+    // A time of day that already passed today means tomorrow
+    if due <= now && !day_given {
+        date = date.succ_opt()?;
+        due = local_to_utc(tz, date.and_time(time))?;
+    }
+
+    Some((due, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{Asia::Tokyo, Europe::Berlin};
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    /// A Friday morning.
+    fn now() -> DateTime<Utc> {
+        utc("2021-12-17T10:00:00Z")
+    }
+
+    fn when(text: &str) -> Option<(DateTime<Utc>, usize)> {
+        let words = text.split_whitespace().collect::<Vec<&str>>();
+        parse_when(&words, now(), &Utc)
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("2h30m"), Some(Duration::minutes(150)));
+        assert_eq!(parse_duration("1W"), Some(Duration::weeks(1)));
+
+        for text in ["", "10", "5x", "0m", "h", "9999999d"] {
+            assert_eq!(parse_duration(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn parses_times_of_day() {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0);
+
+        assert_eq!(parse_time_of_day("9am"), time(9, 0));
+        assert_eq!(parse_time_of_day("12am"), time(0, 0));
+        assert_eq!(parse_time_of_day("12pm"), time(12, 0));
+        assert_eq!(parse_time_of_day("9:30PM"), time(21, 30));
+        assert_eq!(parse_time_of_day("21:30"), time(21, 30));
+
+        for text in ["9", "13pm", "0am", "25:00", "9:5", "noon"] {
+            assert_eq!(parse_time_of_day(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn parses_relative_times() {
+        assert_eq!(
+            when("in 2h30m take the pizza out"),
+            Some((utc("2021-12-17T12:30:00Z"), 2))
+        );
+        assert_eq!(
+            when("1h 30m stretch"),
+            Some((utc("2021-12-17T11:30:00Z"), 2))
+        );
+        assert_eq!(when("in a while"), None);
+    }
+
+    #[test]
+    fn parses_days_and_times() {
+        assert_eq!(
+            when("tomorrow 18:00 meeting"),
+            Some((utc("2021-12-18T18:00:00Z"), 2))
+        );
+        assert_eq!(
+            when("tomorrow at 9am"),
+            Some((utc("2021-12-18T09:00:00Z"), 3))
+        );
+        assert_eq!(when("tomorrow"), Some((utc("2021-12-18T10:00:00Z"), 1)));
+        assert_eq!(
+            when("2021-12-24 18:00"),
+            Some((utc("2021-12-24T18:00:00Z"), 2))
+        );
+    }
+
+    #[test]
+    fn moves_past_times_to_tomorrow() {
+        assert_eq!(when("11:00"), Some((utc("2021-12-17T11:00:00Z"), 1)));
+        assert_eq!(when("9am"), Some((utc("2021-12-18T09:00:00Z"), 1)));
+        // Unless the day was given
+        assert_eq!(when("today 9am"), Some((utc("2021-12-17T09:00:00Z"), 2)));
+    }
+
+    #[test]
+    fn reads_times_in_the_timezone() {
+        let words = ["tomorrow", "9am"];
+        // It's already 19:00 on Friday in Tokyo
+        assert_eq!(
+            parse_when(&words, now(), &Tokyo),
+            Some((utc("2021-12-18T00:00:00Z"), 2))
+        );
+        assert_eq!(
+            parse_when(&["in", "1h"], now(), &Tokyo),
+            Some((utc("2021-12-17T11:00:00Z"), 2))
+        );
+    }
+
+    #[test]
+    fn moves_times_past_daylight_saving_gaps() {
+        // Berlin goes from 2:00 to 3:00 on this night
+        let words = ["2022-03-27", "2:30"];
+        assert_eq!(
+            parse_when(&words, utc("2022-03-20T10:00:00Z"), &Berlin),
+            Some((utc("2022-03-27T01:30:00Z"), 2))
+        );
+    }
+
+    #[test]
+    fn rejects_other_text() {
+        for text in [
+            "",
+            "hello there",
+            "at",
+            "at noon",
+            "tomorrow at",
+            "2021-13-01 9am",
+        ] {
+            assert_eq!(when(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::minutes(90)), "1h 30m");
+        assert_eq!(format_duration(Duration::days(8)), "1w 1d");
+        assert_eq!(format_duration(Duration::zero()), "0s");
+    }
+}